@@ -0,0 +1,45 @@
+use std::io::{self, Read, Seek, SeekFrom};
+
+/// A view of the `len` bytes starting at `base` of a file, so offsets relative to the start of a SIR0 block can be
+/// used when it isn't at the start of the file.
+pub(crate) struct Block<'a, F> {
+    file: &'a mut F,
+    base: u64,
+    len: u64,
+    position: u64,
+}
+
+impl<'a, F: Seek> Block<'a, F> {
+    pub(crate) fn new(file: &'a mut F, base: u64, len: u64) -> io::Result<Block<'a, F>> {
+        file.seek(SeekFrom::Start(base))?;
+        Ok(Block {
+            file,
+            base,
+            len,
+            position: 0,
+        })
+    }
+}
+
+impl<F: Read> Read for Block<'_, F> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let remaining = self.len.saturating_sub(self.position);
+        let to_read = (buf.len() as u64).min(remaining) as usize;
+        let read = self.file.read(&mut buf[..to_read])?;
+        self.position += read as u64;
+        Ok(read)
+    }
+}
+
+impl<F: Seek> Seek for Block<'_, F> {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        let new_position = match pos {
+            SeekFrom::Start(offset) => Some(offset),
+            SeekFrom::Current(offset) => self.position.checked_add_signed(offset),
+            SeekFrom::End(offset) => self.len.checked_add_signed(offset),
+        }.ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "seek before the start of the block"))?;
+        self.file.seek(SeekFrom::Start(self.base + new_position))?;
+        self.position = new_position;
+        Ok(new_position)
+    }
+}
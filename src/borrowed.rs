@@ -0,0 +1,136 @@
+use crate::{ParseOptions, ParseWarning, ScriptEntry, ScriptEntryList, ScriptEntryListError};
+use crate::footer::sir0_footer_extent;
+use crate::script_entry_list::{read_content_header, read_entries_into};
+use crate::string_codec::{read_referenced_string, Utf16LeCodec};
+use std::borrow::Cow;
+use std::convert::TryFrom;
+use std::io::{self, Cursor};
+
+/// A [`ScriptEntry`] parsed by [`ScriptEntryList::parse_borrowed`]. The utf-8 names borrow from the parsed buffer,
+/// while the utf-16 paths are always decoded to an owned string.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScriptEntryRef<'a> {
+    pub entity_name: Cow<'a, str>,
+    pub map_name: Cow<'a, str>,
+    pub lua_path: Cow<'a, str>,
+    pub plb_path: Cow<'a, str>,
+    pub flags: [u32; 4],
+}
+
+impl ScriptEntryRef<'_> {
+    pub fn into_owned(self) -> ScriptEntry {
+        ScriptEntry {
+            entity_name: self.entity_name.into_owned(),
+            map_name: self.map_name.into_owned(),
+            lua_path: self.lua_path.into_owned(),
+            plb_path: self.plb_path.into_owned(),
+            flags: self.flags,
+        }
+    }
+}
+
+fn u32_at(buffer: &[u8], offset: u64) -> Result<u32, ScriptEntryListError> {
+    let bytes = usize::try_from(offset).ok()
+        .and_then(|offset| buffer.get(offset..offset.checked_add(4)?))
+        .ok_or_else(|| io::Error::from(io::ErrorKind::UnexpectedEof))?;
+    Ok(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+}
+
+/// Borrow the NUL-terminated utf-8 string at `reference`, with the same rules as `read_referenced_string`.
+fn borrow_utf8(buffer: &[u8], reference: u64, max_len: Option<usize>) -> Result<&str, ScriptEntryListError> {
+    if reference == 0 {
+        return Ok("");
+    };
+    let start = usize::try_from(reference).ok()
+        .filter(|start| *start < buffer.len())
+        .ok_or_else(|| io::Error::from(io::ErrorKind::UnexpectedEof))?;
+    let rest = &buffer[start..];
+    let max_len = max_len.unwrap_or(rest.len());
+    let len = match rest.iter().take(max_len.saturating_add(1)).position(|byte| *byte == 0) {
+        Some(len) => len,
+        None if rest.len() > max_len => return Err(ScriptEntryListError::UnterminatedString { offset: reference }),
+        None => return Err(io::Error::from(io::ErrorKind::UnexpectedEof).into()),
+    };
+    match std::str::from_utf8(&rest[..len]) {
+        Ok(string) => Ok(string),
+        // to report the error the same way as the owned parser
+        Err(_) => Err(String::from_utf8(rest[..len].to_vec()).unwrap_err().into()),
+    }
+}
+
+impl ScriptEntryList {
+    /// Parse an entry list file already in memory, borrowing the utf-8 strings from `buffer` instead of
+    /// allocating them.
+    ///
+    /// This use the default [`ParseOptions`].
+    pub fn parse_borrowed(buffer: &[u8]) -> Result<Vec<ScriptEntryRef<'_>>, ScriptEntryListError> {
+        ScriptEntryList::parse_borrowed_with_options(buffer, &ParseOptions::default())
+    }
+
+    /// Same as [`ScriptEntryList::parse_borrowed`], with the given options. Only [`ParseOptions::magic`] and
+    /// [`ParseOptions::max_string_len`] are used, as the strings are always read as utf-8 names and utf-16 paths.
+    pub fn parse_borrowed_with_options<'a>(buffer: &'a [u8], options: &ParseOptions) -> Result<Vec<ScriptEntryRef<'a>>, ScriptEntryListError> {
+        let max_len = options.max_string_len;
+        let mut cursor = Cursor::new(buffer);
+        let header = read_content_header(&mut cursor, &options.magic)?;
+
+        let mut entries = Vec::new();
+        for entryid in 0..header.entry_count as u64 {
+            let record = u32_at(buffer, header.pointer_entry_list as u64 + entryid * 4)? as u64;
+            if record + 20 > buffer.len() as u64 {
+                return Err(ScriptEntryListError::TruncatedEntryRecord { index: entryid as usize, offset: record });
+            };
+            let field = |field_id: u64| -> Result<u64, ScriptEntryListError> {
+                Ok(u32_at(buffer, record + field_id * 4)? as u64)
+            };
+
+            let flags_pointer = field(4)?;
+            let mut flags = [0; 4];
+            for (flag_id, flag) in flags.iter_mut().enumerate() {
+                *flag = u32_at(buffer, flags_pointer + flag_id as u64 * 4)?;
+            }
+
+            entries.push(ScriptEntryRef {
+                entity_name: Cow::Borrowed(borrow_utf8(buffer, field(0)?, max_len)?),
+                map_name: Cow::Borrowed(borrow_utf8(buffer, field(1)?, max_len)?),
+                lua_path: Cow::Owned(read_referenced_string(&mut cursor, field(2)?, &Utf16LeCodec, max_len)?),
+                plb_path: Cow::Owned(read_referenced_string(&mut cursor, field(3)?, &Utf16LeCodec, max_len)?),
+                flags,
+            });
+        }
+        Ok(entries)
+    }
+
+    /// Parse the entry list file at the start of `input`, then advance `input` past it, so the data that follows
+    /// can be read by the caller.
+    ///
+    /// As in a standalone file, the pointers are relative to the start of the block, which is the start of
+    /// `input`. The block is considered to end with the SIR0 footer, once the last pointer of the entries is
+    /// listed: the final padding, if any, can't be told apart from the data that follows, and so is left in
+    /// `input`.
+    pub fn new_from_slice(input: &mut &[u8]) -> Result<ScriptEntryList, ScriptEntryListError> {
+        Ok(ScriptEntryList::new_from_slice_with_options(input, &ParseOptions::default())?.0)
+    }
+
+    /// Same as [`ScriptEntryList::new_from_slice`], with the given options.
+    pub fn new_from_slice_with_options(input: &mut &[u8], options: &ParseOptions) -> Result<(ScriptEntryList, Vec<ParseWarning>), ScriptEntryListError> {
+        let buffer = *input;
+        let mut entries = Vec::new();
+        let mut layouts = Vec::new();
+        let mut warnings = Vec::new();
+        read_entries_into(&mut Cursor::new(buffer), &mut entries, &mut layouts, options, &mut warnings)?;
+        let list = ScriptEntryList::from_entries(entries);
+        let footer = u32_at(buffer, 8)? as usize;
+        let footer_data = buffer.get(footer..).ok_or_else(|| io::Error::from(io::ErrorKind::UnexpectedEof))?;
+
+        // the last pointer is the flags pointer of the last record, or the entry pointer list pointer of the content
+        // data header if there is no entry
+        let last_pointer = match layouts.iter().map(|layout| layout.record).max() {
+            Some(record) => record + 16,
+            None => u32_at(buffer, 4)? as u64 + 4,
+        };
+        let footer_len = sir0_footer_extent(footer_data, Some(last_pointer));
+        *input = &buffer[footer + footer_len..];
+        Ok((list, warnings))
+    }
+}
@@ -0,0 +1,100 @@
+use crate::{EntryOrder, FieldCodecs, FooterWriter, ParseOptions, ParseWarning, ScriptEntryList, ScriptEntryListError, StringOrder, WriteOptions};
+use std::io::{Read, Seek, Write};
+use std::sync::Arc;
+
+/// Both the parse and write options, configured together, to read and write files of the same format.
+#[derive(Debug, Clone, Default)]
+pub struct ScriptEntryListCodec {
+    pub parse_options: ParseOptions,
+    pub write_options: WriteOptions,
+}
+
+impl ScriptEntryListCodec {
+    /// The same options as [`ScriptEntryList::new_from_file`] and [`ScriptEntryList::write_to_file`].
+    pub fn new() -> ScriptEntryListCodec {
+        ScriptEntryListCodec::default()
+    }
+
+    /// The string encodings, used for both reading and writing.
+    pub fn codecs(mut self, codecs: FieldCodecs) -> ScriptEntryListCodec {
+        self.parse_options.codecs = codecs.clone();
+        self.write_options.codecs = codecs;
+        self
+    }
+
+    /// Write a null pointer after the entry pointer list, and use it rather than the entry count when reading.
+    pub fn pointer_list_sentinel(mut self, sentinel: bool) -> ScriptEntryListCodec {
+        self.parse_options.pointer_list_sentinel = sentinel;
+        self.write_options.pointer_list_sentinel = sentinel;
+        self
+    }
+
+    /// See [`ParseOptions::strict`].
+    pub fn strict(mut self, strict: bool) -> ScriptEntryListCodec {
+        self.parse_options.strict = strict;
+        self
+    }
+
+    /// See [`ParseOptions::repair_entry_count`].
+    pub fn repair_entry_count(mut self, repair: bool) -> ScriptEntryListCodec {
+        self.parse_options.repair_entry_count = repair;
+        self
+    }
+
+    /// See [`ParseOptions::max_string_len`].
+    pub fn max_string_len(mut self, max_len: usize) -> ScriptEntryListCodec {
+        self.parse_options.max_string_len = Some(max_len);
+        self
+    }
+
+    /// See [`WriteOptions::final_alignment`].
+    pub fn final_alignment(mut self, alignment: usize) -> ScriptEntryListCodec {
+        self.write_options.final_alignment = alignment;
+        self
+    }
+
+    /// See [`WriteOptions::entry_order`].
+    pub fn entry_order(mut self, order: EntryOrder) -> ScriptEntryListCodec {
+        self.write_options.entry_order = order;
+        self
+    }
+
+    /// See [`WriteOptions::string_order`].
+    pub fn string_order(mut self, order: StringOrder) -> ScriptEntryListCodec {
+        self.write_options.string_order = order;
+        self
+    }
+
+    /// See [`WriteOptions::content_header_len`].
+    pub fn content_header_len(mut self, len: usize) -> ScriptEntryListCodec {
+        self.write_options.content_header_len = len;
+        self
+    }
+
+    /// See [`WriteOptions::dedup_flags`].
+    pub fn dedup_flags(mut self, dedup: bool) -> ScriptEntryListCodec {
+        self.write_options.dedup_flags = dedup;
+        self
+    }
+
+    /// Set the magic both read and written. See [`ParseOptions::magic`].
+    pub fn magic(mut self, magic: [u8; 4]) -> ScriptEntryListCodec {
+        self.parse_options.magic = magic;
+        self.write_options.magic = magic;
+        self
+    }
+
+    /// See [`WriteOptions::footer_writer`].
+    pub fn footer_writer(mut self, footer_writer: Arc<dyn FooterWriter>) -> ScriptEntryListCodec {
+        self.write_options.footer_writer = footer_writer;
+        self
+    }
+
+    pub fn read<F: Read + Seek>(&self, file: &mut F) -> Result<(ScriptEntryList, Vec<ParseWarning>), ScriptEntryListError> {
+        ScriptEntryList::new_from_file_with_options(file, &self.parse_options)
+    }
+
+    pub fn write<F: Write + Seek>(&self, file: &mut F, list: &ScriptEntryList) -> Result<(), ScriptEntryListError> {
+        list.write_to_file_with_options(file, &self.write_options)
+    }
+}
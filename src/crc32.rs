@@ -0,0 +1,30 @@
+use crate::{ScriptEntryList, ScriptEntryListError};
+use std::io::{Read, Seek, SeekFrom};
+
+/// The CRC-32 (IEEE 802.3, as used by zip and png) of `data`
+pub(crate) fn crc32(data: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for byte in data {
+        crc ^= *byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 { (crc >> 1) ^ 0xEDB8_8320 } else { crc >> 1 };
+        }
+    }
+    !crc
+}
+
+impl ScriptEntryList {
+    /// Check the last 4 bytes of `file` are the CRC-32 of everything before, as appended with
+    /// [`crate::WriteOptions::append_crc32`].
+    pub fn verify_crc32<F: Read + Seek>(file: &mut F) -> Result<bool, ScriptEntryListError> {
+        let len = file.seek(SeekFrom::End(0))?;
+        if len < 4 {
+            return Err(ScriptEntryListError::TooSmall { len });
+        };
+        file.seek(SeekFrom::Start(0))?;
+        let mut data = Vec::new();
+        file.read_to_end(&mut data)?;
+        let (content, trailer) = data.split_at(data.len() - 4);
+        Ok(crc32(content) == u32::from_le_bytes([trailer[0], trailer[1], trailer[2], trailer[3]]))
+    }
+}
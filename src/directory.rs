@@ -0,0 +1,63 @@
+use crate::{ScriptEntry, ScriptEntryList, ScriptEntryListError};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Add every file under `dir` to `files`, recursively. Symbolic links are skipped, so a link to a parent
+/// directory can't make it recurse forever.
+fn list_files(dir: &Path, files: &mut Vec<PathBuf>) -> Result<(), ScriptEntryListError> {
+    for dir_entry in fs::read_dir(dir)? {
+        let dir_entry = dir_entry?;
+        let file_type = dir_entry.file_type()?;
+        if file_type.is_symlink() {
+            continue;
+        } else if file_type.is_dir() {
+            list_files(&dir_entry.path(), files)?;
+        } else {
+            files.push(dir_entry.path());
+        };
+    }
+    Ok(())
+}
+
+/// `path` relative to `root`, with `/` as separator
+fn relative_path_string(root: &Path, path: &Path) -> String {
+    let relative = path.strip_prefix(root).unwrap_or(path);
+    relative.components()
+        .map(|component| component.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+impl ScriptEntryList {
+    /// Build a list with one entry per `.lua` file under `root` that has a `.plb` file with the same name next to
+    /// it, sorted by path.
+    ///
+    /// The paths of the entries are relative to `root`, with `/` as separator. `naming` is given the path of the
+    /// lua file (also relative to `root`) and return the entity name and the map name of the entry. The flags are
+    /// set to zero. Symbolic links (to files or directories) under `root` are ignored.
+    pub fn from_script_directory(root: &Path, naming: &dyn Fn(&Path) -> (String, String)) -> Result<ScriptEntryList, ScriptEntryListError> {
+        let mut files = Vec::new();
+        list_files(root, &mut files)?;
+        files.sort();
+
+        let mut entries = Vec::new();
+        for lua_path in &files {
+            if lua_path.extension().map_or(true, |extension| extension != "lua") {
+                continue;
+            };
+            let plb_path = lua_path.with_extension("plb");
+            if files.binary_search(&plb_path).is_err() {
+                continue;
+            };
+            let (entity_name, map_name) = naming(lua_path.strip_prefix(root).unwrap_or(lua_path));
+            entries.push(ScriptEntry {
+                entity_name,
+                map_name,
+                lua_path: relative_path_string(root, lua_path),
+                plb_path: relative_path_string(root, &plb_path),
+                flags: [0; 4],
+            });
+        }
+        Ok(ScriptEntryList::from_entries(entries))
+    }
+}
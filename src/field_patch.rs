@@ -0,0 +1,89 @@
+use crate::{EntryLayout, ScriptEntryList, ScriptEntryListError};
+use crate::string_codec::{encode_field, FieldCodecs};
+use std::io::{Read, Seek, SeekFrom, Write};
+
+/// A new value for one field of an entry.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EntryField {
+    EntityName(String),
+    MapName(String),
+    LuaPath(String),
+    PlbPath(String),
+    Flags([u32; 4]),
+}
+
+/// How [`ScriptEntryList::patch_entry_field`] applied the change.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FieldPatchOutcome {
+    /// Only the bytes of the field were overwritten
+    InPlace,
+    /// The whole file was rewritten, and is `len` bytes long. If the file was longer before, the bytes after
+    /// `len` are left as is, and should be truncated by the caller.
+    Rewritten { len: u64 },
+}
+
+/// Return the number of fields in `layouts` pointing to `offset`
+pub(crate) fn reference_count(layouts: &[EntryLayout], offset: u64) -> usize {
+    layouts.iter()
+        .map(|layout| [layout.entity_name, layout.map_name, layout.lua_path, layout.plb_path, layout.flags]
+            .iter()
+            .filter(|pointer| **pointer == offset)
+            .count())
+        .sum()
+}
+
+impl ScriptEntryList {
+    /// Change one field of the entry at `index` of an existing file.
+    ///
+    /// If the new value fit in the space taken by the old one, and no other field share that space, only
+    /// those bytes are overwritten (the rest of the old string is filled with zero). Otherwise, the whole file is
+    /// rewritten as [`ScriptEntryList::write_to_file`] would.
+    pub fn patch_entry_field<F: Read + Write + Seek>(file: &mut F, index: usize, field: EntryField) -> Result<FieldPatchOutcome, ScriptEntryListError> {
+        let (mut list, layouts) = ScriptEntryList::new_from_file_with_layout(file)?;
+        let layout = layouts.get(index).ok_or(ScriptEntryListError::InvalidEntryIndex(index))?;
+        let entry = &list.entries[index];
+
+        // the offset of the field, the space allocated to it and the new bytes (NUL terminator included)
+        let codecs = FieldCodecs::default();
+        let (offset, allocated, new_bytes) = match &field {
+            EntryField::EntityName(new) | EntryField::MapName(new) | EntryField::LuaPath(new) | EntryField::PlbPath(new) => {
+                let (offset, old, codec) = match &field {
+                    EntryField::EntityName(_) => (layout.entity_name, &entry.entity_name, &codecs.entity_name),
+                    EntryField::MapName(_) => (layout.map_name, &entry.map_name, &codecs.map_name),
+                    EntryField::LuaPath(_) => (layout.lua_path, &entry.lua_path, &codecs.lua_path),
+                    _ => (layout.plb_path, &entry.plb_path, &codecs.plb_path),
+                };
+                let allocated = (codec.encode(old).len() + codec.unit_size()) as u64;
+                (offset, allocated, encode_field(&**codec, new)?)
+            },
+            EntryField::Flags(flags) => {
+                let mut new_bytes = Vec::new();
+                for flag in flags {
+                    new_bytes.extend_from_slice(&u32::to_le_bytes(*flag));
+                }
+                (layout.flags, 16, new_bytes)
+            },
+        };
+
+        // a null pointer doesn't point to any allocated space
+        if offset != 0 && new_bytes.len() as u64 <= allocated && reference_count(&layouts, offset) == 1 {
+            file.seek(SeekFrom::Start(offset))?;
+            file.write_all(&new_bytes)?;
+            file.write_all(&vec![0; (allocated - new_bytes.len() as u64) as usize])?;
+            return Ok(FieldPatchOutcome::InPlace);
+        };
+
+        let entry = &mut list.entries[index];
+        match field {
+            EntryField::EntityName(name) => entry.entity_name = name,
+            EntryField::MapName(name) => entry.map_name = name,
+            EntryField::LuaPath(path) => entry.lua_path = path,
+            EntryField::PlbPath(path) => entry.plb_path = path,
+            EntryField::Flags(flags) => entry.flags = flags,
+        };
+        list.write_to_file(file)?;
+        Ok(FieldPatchOutcome::Rewritten {
+            len: file.stream_position()?,
+        })
+    }
+}
@@ -0,0 +1,98 @@
+//! Named access to the bits of [`ScriptEntry::flags`].
+//!
+//! The meaning of the flags isn't documented yet, so no named mask is provided here: none of them would be
+//! confirmed. Tools can define their own with [`FlagMask::bit`], and confirmed ones should be added here.
+
+use crate::ScriptEntry;
+
+/// Some bits of one of the 4 flag words of an entry.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct FlagMask {
+    /// the index of the flag word, from 0 to 3
+    pub word: usize,
+    pub mask: u32,
+}
+
+impl FlagMask {
+    /// The mask of a single bit of a flag word. Panic if `word` is more than 3 or `bit` more than 31.
+    pub const fn bit(word: usize, bit: u8) -> FlagMask {
+        assert!(word < 4 && bit < 32);
+        FlagMask {
+            word,
+            mask: 1 << bit,
+        }
+    }
+}
+
+/// The flags of an entry, as a standalone value. Every bit is kept as is, including those without a known meaning.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct FlagSet(pub [u32; 4]);
+
+impl FlagSet {
+    /// Return true if every bit of `flag` is set.
+    pub fn get(&self, flag: FlagMask) -> bool {
+        self.0[flag.word] & flag.mask == flag.mask
+    }
+
+    /// Set or clear every bit of `flag`.
+    pub fn set(&mut self, flag: FlagMask, value: bool) {
+        if value {
+            self.0[flag.word] |= flag.mask;
+        } else {
+            self.0[flag.word] &= !flag.mask;
+        }
+    }
+}
+
+impl From<[u32; 4]> for FlagSet {
+    fn from(flags: [u32; 4]) -> FlagSet {
+        FlagSet(flags)
+    }
+}
+
+impl From<FlagSet> for [u32; 4] {
+    fn from(flags: FlagSet) -> [u32; 4] {
+        flags.0
+    }
+}
+
+impl ScriptEntry {
+    /// Return true if every bit of `flag` is set.
+    pub fn has_flag(&self, flag: FlagMask) -> bool {
+        self.flag_set().get(flag)
+    }
+
+    /// Set or clear every bit of `flag`.
+    pub fn set_flag(&mut self, flag: FlagMask, value: bool) {
+        let mut flags = self.flag_set();
+        flags.set(flag, value);
+        self.set_flag_set(flags);
+    }
+
+    /// Every flag bit, bit `n` of word `w` being at index `w * 32 + n`.
+    pub fn flags_as_bits(&self) -> [bool; 128] {
+        let mut bits = [false; 128];
+        for (index, bit) in bits.iter_mut().enumerate() {
+            *bit = self.flags[index / 32] & (1 << (index % 32)) != 0;
+        }
+        bits
+    }
+
+    /// Set the flags from bits laid out as in [`ScriptEntry::flags_as_bits`].
+    pub fn set_flags_from_bits(&mut self, bits: &[bool; 128]) {
+        self.flags = [0; 4];
+        for (index, bit) in bits.iter().enumerate() {
+            if *bit {
+                self.flags[index / 32] |= 1 << (index % 32);
+            };
+        }
+    }
+
+    pub fn flag_set(&self) -> FlagSet {
+        FlagSet(self.flags)
+    }
+
+    pub fn set_flag_set(&mut self, flags: FlagSet) {
+        self.flags = flags.into();
+    }
+}
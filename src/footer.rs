@@ -0,0 +1,47 @@
+use crate::ScriptEntryListError;
+use pmd_sir0::write_sir0_footer;
+use std::fmt;
+
+/// An encoding of the list of pointers, written at the end of a SIR0 block.
+pub trait FooterWriter: fmt::Debug + Send + Sync {
+    /// Encode the offsets of every pointer of the block, given in increasing order.
+    fn encode(&self, pointers: &[u32]) -> Result<Vec<u8>, ScriptEntryListError>;
+}
+
+/// The standard SIR0 footer, as written by `pmd_sir0`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Sir0Footer;
+
+impl FooterWriter for Sir0Footer {
+    fn encode(&self, pointers: &[u32]) -> Result<Vec<u8>, ScriptEntryListError> {
+        let mut footer = Vec::new();
+        write_sir0_footer(&mut footer, pointers)?;
+        Ok(footer)
+    }
+}
+
+/// Return the length of the standard SIR0 footer at the start of `data`, stopping once a pointer at or after
+/// `last_pointer` is decoded, or at a zero byte that doesn't end a pointer (the footer itself never encodes a delta
+/// of zero, but the last byte of a delta multiple of 128 is zero).
+pub(crate) fn sir0_footer_extent(data: &[u8], last_pointer: Option<u64>) -> usize {
+    let mut pointer = 0;
+    let mut delta = 0;
+    let mut in_pointer = false;
+    for (position, byte) in data.iter().enumerate() {
+        if *byte == 0 && !in_pointer {
+            return position;
+        };
+        delta = (delta << 7) | (*byte & 0x7F) as u64;
+        if *byte & 0x80 == 0 {
+            pointer += delta;
+            delta = 0;
+            in_pointer = false;
+            if last_pointer.is_some_and(|last_pointer| pointer >= last_pointer) {
+                return position + 1;
+            };
+        } else {
+            in_pointer = true;
+        };
+    }
+    data.len()
+}
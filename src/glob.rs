@@ -0,0 +1,32 @@
+/// Match `text` against a glob `pattern`, where `*` match any sequence of characters (`/` included) and `?`
+/// any single character. Every other character only match itself.
+pub(crate) fn glob_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+    let (mut pattern_pos, mut text_pos) = (0, 0);
+    // where to resume if the part after the last `*` fail to match: the position after that `*`, and the text
+    // position it was tried at
+    let mut backtrack = None;
+    while text_pos < text.len() {
+        match pattern.get(pattern_pos) {
+            Some('*') => {
+                pattern_pos += 1;
+                backtrack = Some((pattern_pos, text_pos));
+            },
+            Some(chara) if *chara == '?' || *chara == text[text_pos] => {
+                pattern_pos += 1;
+                text_pos += 1;
+            },
+            _ => match backtrack {
+                // let the `*` eat one more character
+                Some((after_star, star_text_pos)) => {
+                    pattern_pos = after_star;
+                    text_pos = star_text_pos + 1;
+                    backtrack = Some((after_star, star_text_pos + 1));
+                },
+                None => return false,
+            },
+        }
+    }
+    pattern[pattern_pos..].iter().all(|chara| *chara == '*')
+}
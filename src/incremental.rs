@@ -0,0 +1,277 @@
+use crate::{ParseOptions, ScriptEntryList, ScriptEntryListError, WriteOptions};
+use crate::crc32::crc32;
+use crate::field_patch::reference_count;
+use crate::footer::sir0_footer_extent;
+use crate::script_entry_list::read_entries_into;
+use crate::string_codec::encode_field;
+use std::collections::HashMap;
+use std::io::{Cursor, Seek, SeekFrom, Write};
+
+fn read_u32_at(buffer: &[u8], offset: u64) -> Result<u32, ScriptEntryListError> {
+    let offset = offset as usize;
+    match buffer.get(offset..offset + 4) {
+        Some(bytes) => Ok(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]])),
+        None => Err(ScriptEntryListError::OutOfBounds { offset: offset as u64, len: buffer.len() as u64 }),
+    }
+}
+
+fn write_u32_at(buffer: &mut [u8], offset: u64, value: u32) -> Result<(), ScriptEntryListError> {
+    let len = buffer.len() as u64;
+    match buffer.get_mut(offset as usize..offset as usize + 4) {
+        Some(bytes) => {
+            bytes.copy_from_slice(&value.to_le_bytes());
+            Ok(())
+        },
+        None => Err(ScriptEntryListError::OutOfBounds { offset, len }),
+    }
+}
+
+fn pad_to(buffer: &mut Vec<u8>, alignment: usize) {
+    while buffer.len() % alignment != 0 {
+        buffer.push(0);
+    }
+}
+
+/// Zero every byte inside one of the `orphans` ranges but outside all the `live` ones, and if `remove`, remove as
+/// much of each such run as possible while keeping the following data 4-byte aligned. `pointers` are the offsets of every
+/// pointer in `data`: both them and the values they hold are updated. Return the number of bytes removed.
+fn reclaim(data: &mut Vec<u8>, pointers: &mut [u32], orphans: &[(u64, u64)], live: &[(u64, u64)], remove: bool) -> Result<u64, ScriptEntryListError> {
+    let mut reclaimable = vec![false; data.len()];
+    for (ranges, value) in [(orphans, true), (live, false)] {
+        for (start, end) in ranges {
+            let len = reclaimable.len() as u64;
+            let range = reclaimable.get_mut(*start as usize..*end as usize)
+                .ok_or(ScriptEntryListError::OutOfBounds { offset: *start.max(end), len })?;
+            for byte in range {
+                *byte = value;
+            }
+        }
+    }
+
+    // the removed ranges, in order
+    let mut removed: Vec<(u64, u64)> = Vec::new();
+    let mut position = 0;
+    while position < data.len() {
+        if !reclaimable[position] {
+            position += 1;
+            continue;
+        };
+        let start = position;
+        while position < data.len() && reclaimable[position] {
+            data[position] = 0;
+            position += 1;
+        }
+        let len = (position - start) / 4 * 4;
+        if remove && len != 0 {
+            removed.push((start as u64, (start + len) as u64));
+        };
+    }
+
+    let relocate = |offset: u64| -> u64 {
+        offset - removed.iter()
+            .take_while(|(start, _)| *start < offset)
+            .map(|(start, end)| end.min(&offset) - start)
+            .sum::<u64>()
+    };
+    for pointer in pointers.iter_mut() {
+        let value = read_u32_at(data, *pointer as u64).unwrap_or(0);
+        write_u32_at(data, *pointer as u64, relocate(value as u64) as u32)?;
+    }
+    for pointer in pointers.iter_mut() {
+        *pointer = relocate(*pointer as u64) as u32;
+    }
+    for (start, end) in removed.iter().rev() {
+        data.drain(*start as usize..*end as usize);
+    }
+    Ok(removed.iter().map(|(start, end)| end - start).sum())
+}
+
+/// Guess the final alignment `original` was written with, as the smallest power of two the end of its footer
+/// (starting at `footer`) is padded to.
+fn original_alignment(original: &[u8], footer: usize) -> usize {
+    let unpadded = footer + sir0_footer_extent(original.get(footer..).unwrap_or(&[]), None);
+    let mut alignment = 1;
+    while alignment <= original.len() {
+        if (unpadded + alignment - 1) / alignment * alignment == original.len() {
+            return alignment;
+        };
+        alignment *= 2;
+    }
+    1
+}
+
+impl ScriptEntryList {
+    /// Write this list reusing the layout of `original`, an existing entry list file, so most of it stays
+    /// byte-identical.
+    ///
+    /// Everything before the footer of `original` is kept. Strings already in it are pointed to where they are,
+    /// and new ones are appended after it. If the number of entries is unchanged, the entry records and
+    /// (unshared) flag blocks are overwritten in place, otherwise new ones are appended too. The footer is then
+    /// rebuilt, and the output padded to the same alignment as `original`. Data that ends up unreferenced is zeroed,
+    /// but left in place (see [`ScriptEntryList::write_incremental_compacting`] to reclaim it).
+    pub fn write_incremental<F: Write + Seek>(&self, original: &[u8], file: &mut F) -> Result<(), ScriptEntryListError> {
+        self.write_incremental_inner(original, file, None, false).map(|_| ())
+    }
+
+    /// Same as [`ScriptEntryList::write_incremental`], for files using the given options. `original` is read with
+    /// the same codecs and magic. As the layout is kept, only the codecs, magic, final alignment, footer writer and
+    /// CRC-32 trailer options are used.
+    pub fn write_incremental_with_options<F: Write + Seek>(&self, original: &[u8], file: &mut F, options: &WriteOptions) -> Result<(), ScriptEntryListError> {
+        self.write_incremental_inner(original, file, Some(options), false).map(|_| ())
+    }
+
+    /// Same as [`ScriptEntryList::write_incremental`], but the strings, flag blocks and entry records of
+    /// `original` that end up unreferenced (for example, after an entry is removed) are reclaimed, moving the
+    /// following data back. Bytes that can't be removed without breaking alignment are zeroed.
+    ///
+    /// Return the number of bytes removed.
+    pub fn write_incremental_compacting<F: Write + Seek>(&self, original: &[u8], file: &mut F) -> Result<u64, ScriptEntryListError> {
+        self.write_incremental_inner(original, file, None, true)
+    }
+
+    /// Same as [`ScriptEntryList::write_incremental_compacting`], with the options described in
+    /// [`ScriptEntryList::write_incremental_with_options`].
+    pub fn write_incremental_compacting_with_options<F: Write + Seek>(&self, original: &[u8], file: &mut F, options: &WriteOptions) -> Result<u64, ScriptEntryListError> {
+        self.write_incremental_inner(original, file, Some(options), true)
+    }
+
+    /// Without `options`, the default ones are used, with the final alignment of `original`.
+    fn write_incremental_inner<F: Write + Seek>(&self, original: &[u8], file: &mut F, options: Option<&WriteOptions>, compact: bool) -> Result<u64, ScriptEntryListError> {
+        if original.len() < 16 {
+            return Err(ScriptEntryListError::TooSmall { len: original.len() as u64 });
+        };
+        let content_data = read_u32_at(original, 4)? as u64;
+        let original_footer = read_u32_at(original, 8)? as usize;
+        if original_footer > original.len() {
+            return Err(ScriptEntryListError::OutOfBounds { offset: original_footer as u64, len: original.len() as u64 });
+        };
+        let options = match options {
+            Some(options) => options.clone(),
+            None => WriteOptions {
+                final_alignment: original_alignment(original, original_footer),
+                ..WriteOptions::default()
+            },
+        };
+
+        let parse_options = ParseOptions {
+            codecs: options.codecs.clone(),
+            magic: options.magic,
+            ..ParseOptions::default()
+        };
+        let mut original_entries = Vec::new();
+        let mut layouts = Vec::new();
+        read_entries_into(&mut Cursor::new(original), &mut original_entries, &mut layouts, &parse_options, &mut Vec::new())?;
+
+        // the existing string pool, indexed by code unit size and encoded string
+        let codecs = &options.codecs;
+        let mut pool: HashMap<(usize, Vec<u8>), u64> = HashMap::new();
+        // the ranges of the original data, that may be reclaimed once unreferenced
+        let mut orphans = Vec::new();
+        for (entry, layout) in original_entries.iter().zip(&layouts) {
+            for (codec, string, offset) in [
+                (&codecs.entity_name, &entry.entity_name, layout.entity_name),
+                (&codecs.map_name, &entry.map_name, layout.map_name),
+                (&codecs.lua_path, &entry.lua_path, layout.lua_path),
+                (&codecs.plb_path, &entry.plb_path, layout.plb_path),
+            ] {
+                if offset != 0 {
+                    let encoded = encode_field(&**codec, string)?;
+                    orphans.push((offset, offset + encoded.len() as u64));
+                    pool.entry((codec.unit_size(), encoded)).or_insert(offset);
+                };
+            }
+            orphans.push((layout.flags, layout.flags + 16));
+            orphans.push((layout.record, layout.record + 20));
+        }
+        let original_pointer_entry_list = read_u32_at(original, content_data + 4)? as u64;
+        orphans.push((original_pointer_entry_list, original_pointer_entry_list + layouts.len() as u64 * 4));
+        let data_end = orphans.iter().map(|(_, end)| *end).fold(content_data + 8, u64::max);
+        if data_end > original_footer as u64 {
+            return Err(ScriptEntryListError::FooterInsideData { footer: original_footer as u64, data_end });
+        };
+        let mut data = original[..original_footer].to_vec();
+
+        let in_place = self.entries.len() == original_entries.len();
+        let (pointer_entry_list, records) = if in_place {
+            (original_pointer_entry_list, layouts.iter().map(|layout| layout.record).collect())
+        } else {
+            pad_to(&mut data, 4);
+            let pointer_entry_list = data.len() as u64;
+            let records_start = pointer_entry_list + self.entries.len() as u64 * 4;
+            data.resize((records_start + self.entries.len() as u64 * 20) as usize, 0);
+            (pointer_entry_list, (0..self.entries.len() as u64).map(|id| records_start + id * 20).collect::<Vec<_>>())
+        };
+        let mut live = vec![(pointer_entry_list, pointer_entry_list + self.entries.len() as u64 * 4)];
+        write_u32_at(&mut data, content_data, self.entries.len() as u32)?;
+        write_u32_at(&mut data, content_data + 4, pointer_entry_list as u32)?;
+
+        let mut sir0_pointers = vec![4, 8, (content_data + 4) as u32];
+        for (entryid, (entry, record)) in self.entries.iter().zip(&records).enumerate() {
+            let table_slot = pointer_entry_list + entryid as u64 * 4;
+            write_u32_at(&mut data, table_slot, *record as u32)?;
+            sir0_pointers.push(table_slot as u32);
+
+            let flags_offset = match layouts.get(entryid) {
+                Some(layout) if in_place && reference_count(&layouts, layout.flags) == 1 => layout.flags,
+                _ => {
+                    pad_to(&mut data, 4);
+                    let offset = data.len() as u64;
+                    data.resize(data.len() + 16, 0);
+                    offset
+                },
+            };
+            live.push((*record, *record + 20));
+            live.push((flags_offset, flags_offset + 16));
+            for (flag_id, flag) in entry.flags.iter().enumerate() {
+                write_u32_at(&mut data, flags_offset + flag_id as u64 * 4, *flag)?;
+            }
+
+            let mut fields = Vec::new();
+            for (codec, string) in [
+                (&codecs.entity_name, &entry.entity_name),
+                (&codecs.map_name, &entry.map_name),
+                (&codecs.lua_path, &entry.lua_path),
+                (&codecs.plb_path, &entry.plb_path),
+            ] {
+                let key = (codec.unit_size(), encode_field(&**codec, string)?);
+                let len = key.1.len() as u64;
+                let offset = match pool.get(&key) {
+                    Some(offset) => *offset,
+                    None => {
+                        pad_to(&mut data, codec.unit_size());
+                        let offset = data.len() as u64;
+                        data.extend_from_slice(&key.1);
+                        pool.insert(key, offset);
+                        offset
+                    },
+                };
+                live.push((offset, offset + len));
+                fields.push(offset);
+            }
+            fields.push(flags_offset);
+
+            for (field_id, offset) in fields.iter().enumerate() {
+                let pointer = record + field_id as u64 * 4;
+                write_u32_at(&mut data, pointer, *offset as u32)?;
+                sir0_pointers.push(pointer as u32);
+            }
+        }
+
+        let reclaimed = reclaim(&mut data, &mut sir0_pointers, &orphans, &live, compact)?;
+
+        pad_to(&mut data, 4);
+        let footer = data.len();
+        write_u32_at(&mut data, 8, footer as u32)?;
+        sir0_pointers.sort_unstable();
+        data.extend(options.footer_writer.encode(&sir0_pointers)?);
+        pad_to(&mut data, options.final_alignment);
+        if options.append_crc32 {
+            let crc = crc32(&data);
+            data.extend_from_slice(&crc.to_le_bytes());
+        };
+
+        file.seek(SeekFrom::Start(0))?;
+        file.write_all(&data)?;
+        Ok(reclaimed)
+    }
+}
@@ -0,0 +1,354 @@
+use crate::{EntryOrder, ParseOptions, ScriptEntry, ScriptEntryList, ScriptEntryListError, StringOrder, WriteOptions};
+use serde::Serialize;
+use crate::script_entry_list::{read_content_header, read_entries_into};
+use crate::string_codec::{encode_field, StringCodec};
+use std::cmp::Reverse;
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::io::{Read, Seek};
+
+/// The offsets of the parts of an entry, in a written or parsed file.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct EntryLayout {
+    /// offset of the 20 bytes record holding the 5 pointers of this entry
+    pub record: u64,
+    pub entity_name: u64,
+    pub map_name: u64,
+    pub lua_path: u64,
+    pub plb_path: u64,
+    pub flags: u64,
+}
+
+/// A string of the string pool, in a written file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StringLayout {
+    pub value: String,
+    pub offset: u64,
+    /// the size of the encoded string, including the NUL terminator
+    pub len: u64,
+    pub(crate) encoded: Vec<u8>,
+}
+
+/// Where every part of a [`ScriptEntryList`] would be placed by [`ScriptEntryList::write_to_file`].
+///
+/// All the offsets are relative to the start of the SIR0 block.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LayoutPlan {
+    pub content_data: u64,
+    pub entry_pointer_list: u64,
+    /// the index in the list of each written entry, in the order they are written
+    pub order: Vec<usize>,
+    /// in the same order than the written entries
+    pub entries: Vec<EntryLayout>,
+    /// in the order they are written
+    pub strings: Vec<StringLayout>,
+    /// start of the SIR0 footer, after the padding
+    pub footer: u64,
+    pub footer_len: u64,
+    /// the total size of the block, including the final padding
+    pub total_len: u64,
+    /// the offsets of every pointer in the block, as written in the SIR0 footer
+    pub(crate) sir0_pointers: Vec<u32>,
+    /// the content of every flag block, in the order they are written
+    pub(crate) flag_blocks: Vec<[u32; 4]>,
+    /// the encoded SIR0 footer. Only filled by [`ScriptEntryList::plan_layout_with_options`].
+    pub(crate) footer_data: Vec<u8>,
+}
+
+/// The result of [`ScriptEntryList::compact`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CompactReport {
+    pub removed_entries: usize,
+    /// the size of the file written with the default options, before compacting
+    pub original_len: u64,
+    /// the size of the file written with [`WriteOptions::compact`], after compacting
+    pub compact_len: u64,
+}
+
+impl CompactReport {
+    pub fn saved(&self) -> u64 {
+        self.original_len - self.compact_len
+    }
+}
+
+/// An entry, alongside where its parts were found in the file.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct AnnotatedEntry {
+    #[serde(flatten)]
+    pub entry: ScriptEntry,
+    pub offsets: EntryLayout,
+}
+
+/// A parsed file with the offsets of everything, meant to be serialized (for example to JSON with `serde_json`)
+/// to drive an hex viewer or another inspector.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct AnnotatedFile {
+    pub entries: Vec<AnnotatedEntry>,
+}
+
+/// The size of the SIR0 encoding of the given pointer list
+fn sir0_footer_len(sir0_pointers: &[u32]) -> u64 {
+    let mut len = 0;
+    let mut previous = 0;
+    for pointer in sir0_pointers {
+        let delta = pointer - previous;
+        previous = *pointer;
+        // 7 bits are stored per byte, and a zero delta is skipped
+        len += ((32 - delta.leading_zeros() + 6) / 7) as u64;
+    }
+    len
+}
+
+/// Encode a string with its NUL terminator, also returning what its string pool is keyed on
+fn encode_pooled<'a>(codec: &dyn StringCodec, string: &'a str) -> (&'a str, usize, Vec<u8>) {
+    let mut encoded = codec.encode(string);
+    encoded.extend(std::iter::repeat(0).take(codec.unit_size()));
+    (string, codec.unit_size(), encoded)
+}
+
+fn align(offset: u64, alignment: u64) -> u64 {
+    (offset + alignment - 1) / alignment * alignment
+}
+
+impl ScriptEntryList {
+    /// Parse a file, keeping the offset each field was read from.
+    pub fn read_annotated<F: Read + Seek>(file: &mut F) -> Result<AnnotatedFile, ScriptEntryListError> {
+        let (list, layouts) = ScriptEntryList::new_from_file_with_layout(file)?;
+        Ok(AnnotatedFile {
+            entries: list.entries.into_iter()
+                .zip(layouts)
+                .map(|(entry, offsets)| AnnotatedEntry {
+                    entry,
+                    offsets,
+                })
+                .collect(),
+        })
+    }
+
+    /// Parse a file, and describe where every part of it is, one per line, for investigating unfamiliar files.
+    pub fn debug_dump_offsets<F: Read + Seek>(file: &mut F) -> Result<String, ScriptEntryListError> {
+        ScriptEntryList::debug_dump_offsets_with_options(file, &ParseOptions::default())
+    }
+
+    /// Same as [`ScriptEntryList::debug_dump_offsets`], parsing the file with the given options. Warnings are
+    /// listed at the end.
+    pub fn debug_dump_offsets_with_options<F: Read + Seek>(file: &mut F, options: &ParseOptions) -> Result<String, ScriptEntryListError> {
+        let header = read_content_header(file, &options.magic)?;
+        let mut entries = Vec::new();
+        let mut layouts = Vec::new();
+        let mut warnings = Vec::new();
+        read_entries_into(file, &mut entries, &mut layouts, options, &mut warnings)?;
+        let list = ScriptEntryList::from_entries(entries);
+        let mut dump = String::new();
+        dump.push_str(&format!("SIR0 header: content data at 0x{:x}, footer at 0x{:x}\n", header.pointer_content_data, header.pointer_footer));
+        dump.push_str(&format!("content data: {} entries, entry pointer list at 0x{:x}\n", header.entry_count, header.pointer_entry_list));
+        for (index, (entry, layout)) in list.entries.iter().zip(&layouts).enumerate() {
+            dump.push_str(&format!("entry {}: record at 0x{:x}\n", index, layout.record));
+            dump.push_str(&format!("  entity name at 0x{:x}: {:?}\n", layout.entity_name, entry.entity_name));
+            dump.push_str(&format!("  map name at 0x{:x}: {:?}\n", layout.map_name, entry.map_name));
+            dump.push_str(&format!("  lua path at 0x{:x}: {:?}\n", layout.lua_path, entry.lua_path));
+            dump.push_str(&format!("  plb path at 0x{:x}: {:?}\n", layout.plb_path, entry.plb_path));
+            dump.push_str(&format!("  flags at 0x{:x}: {:08x?}\n", layout.flags, entry.flags));
+        }
+        dump.push_str(&format!("SIR0 footer (and final padding): 0x{:x} to 0x{:x}\n", header.pointer_footer, header.file_len));
+        for warning in &warnings {
+            dump.push_str(&format!("warning: {:?}\n", warning));
+        }
+        Ok(dump)
+    }
+
+    /// Compute where [`ScriptEntryList::write_to_file`] would place every part of the file, without writing it.
+    pub fn plan_layout(&self) -> LayoutPlan {
+        self.plan_layout_unchecked(&WriteOptions::default())
+    }
+
+    /// The exact size of the file [`ScriptEntryList::write_to_file`] would write, string pooling and SIR0 footer
+    /// included.
+    pub fn byte_size_estimate(&self) -> u64 {
+        self.plan_layout().total_len
+    }
+
+    /// Return true if the file [`ScriptEntryList::write_to_file`] would write is at most `budget` bytes long.
+    pub fn fits_in(&self, budget: usize) -> bool {
+        self.byte_size_estimate() <= budget as u64
+    }
+
+    /// Same as [`ScriptEntryList::plan_layout`], for [`ScriptEntryList::write_to_file_with_options`]. This also
+    /// check every string can be encoded.
+    pub fn plan_layout_with_options(&self, options: &WriteOptions) -> Result<LayoutPlan, ScriptEntryListError> {
+        if !options.final_alignment.is_power_of_two() {
+            return Err(ScriptEntryListError::InvalidAlignment(options.final_alignment));
+        };
+        if options.content_header_len < 8 || options.content_header_len % 4 != 0 {
+            return Err(ScriptEntryListError::InvalidContentHeaderLen(options.content_header_len));
+        };
+        let codecs = &options.codecs;
+        for entry in &self.entries {
+            encode_field(&*codecs.entity_name, &entry.entity_name)?;
+            encode_field(&*codecs.map_name, &entry.map_name)?;
+            encode_field(&*codecs.lua_path, &entry.lua_path)?;
+            encode_field(&*codecs.plb_path, &entry.plb_path)?;
+        };
+        let mut plan = self.plan_layout_unchecked(options);
+        // the footer may not be the standard one, so its length is only known once encoded
+        plan.footer_data = options.footer_writer.encode(&plan.sir0_pointers)?;
+        plan.footer_len = plan.footer_data.len() as u64;
+        plan.total_len = align(plan.footer + plan.footer_len, options.final_alignment as u64);
+        Ok(plan)
+    }
+
+    fn plan_layout_unchecked(&self, options: &WriteOptions) -> LayoutPlan {
+        // SIR0 header: magic, pointer to content data, pointer to the footer, and 4 bytes of padding
+        let content_data = 16;
+        // content data header: entry count, pointer to the entry pointer list, and the reserved words
+        let entry_pointer_list = content_data + options.content_header_len as u64;
+        let mut sir0_pointers = vec![4, 8, (content_data + 4) as u32];
+
+        let order = self.entry_order(options.entry_order);
+        let mut position = entry_pointer_list;
+        for _ in 0..self.entries.len() {
+            sir0_pointers.push(position as u32);
+            position += 4;
+        }
+
+        if options.pointer_list_sentinel {
+            position += 4;
+        }
+
+        let records_start = position;
+        for _ in 0..self.entries.len() {
+            for _ in 0..5 {
+                sir0_pointers.push(position as u32);
+                position += 4;
+            }
+        }
+
+        // list of flags
+        // the original compiler doesn't seem to try to elimate double entry, so it is only done on request
+        let mut flags_pointer = Vec::new();
+        let mut flag_blocks = Vec::new();
+        let mut flag_block_map: HashMap<[u32; 4], u64> = HashMap::new();
+        for entryid in &order {
+            let flags = self.entries[*entryid].flags;
+            if options.dedup_flags {
+                // keyed on the exact words, as the position of each bit is meaningful
+                if let Some(shared) = flag_block_map.get(&flags) {
+                    flags_pointer.push(*shared);
+                    continue;
+                };
+                flag_block_map.insert(flags, position);
+            };
+            flags_pointer.push(position);
+            flag_blocks.push(flags);
+            position += 16;
+        }
+
+        // strings
+        // identical strings are only written once. By default, strings with the largest code unit are written first,
+        // so they stay aligned without padding.
+        let codecs = &options.codecs;
+        let entries_fields: Vec<[(&str, usize, Vec<u8>); 4]> = order.iter().map(|entryid| {
+            let entry = &self.entries[*entryid];
+            [
+                encode_pooled(&*codecs.entity_name, &entry.entity_name),
+                encode_pooled(&*codecs.map_name, &entry.map_name),
+                encode_pooled(&*codecs.lua_path, &entry.lua_path),
+                encode_pooled(&*codecs.plb_path, &entry.plb_path),
+            ]
+        }).collect();
+
+        let strings_to_write: Vec<(usize, &str, &Vec<u8>)> = match options.string_order {
+            StringOrder::Sorted => {
+                // sorted by decreasing code unit size, then by string, so the output is deterministic
+                let mut string_to_write_set = BTreeSet::new();
+                for (string, unit_size, encoded) in entries_fields.iter().flatten() {
+                    string_to_write_set.insert((Reverse(*unit_size), *string, encoded));
+                }
+                string_to_write_set.into_iter().map(|(Reverse(unit_size), string, encoded)| (unit_size, string, encoded)).collect()
+            },
+            StringOrder::FirstReferenced => {
+                let mut seen = HashSet::new();
+                entries_fields.iter()
+                    .flatten()
+                    .filter(|(_, unit_size, encoded)| seen.insert((*unit_size, encoded)))
+                    .map(|(string, unit_size, encoded)| (*unit_size, *string, encoded))
+                    .collect()
+            },
+        };
+
+        let mut strings = Vec::new();
+        let mut string_map = HashMap::new();
+        for (unit_size, string, encoded) in strings_to_write {
+            position = align(position, unit_size as u64);
+            string_map.insert((unit_size, encoded), position);
+            strings.push(StringLayout {
+                value: string.to_string(),
+                offset: position,
+                len: encoded.len() as u64,
+                encoded: encoded.clone(),
+            });
+            position += encoded.len() as u64;
+        }
+
+        let entries = entries_fields.iter().enumerate().map(|(entryid, fields)| {
+            let offset = |field_id: usize| {
+                let (_, unit_size, encoded) = &fields[field_id];
+                string_map[&(*unit_size, encoded)]
+            };
+            EntryLayout {
+                record: records_start + entryid as u64 * 20,
+                entity_name: offset(0),
+                map_name: offset(1),
+                lua_path: offset(2),
+                plb_path: offset(3),
+                flags: flags_pointer[entryid],
+            }
+        }).collect();
+
+        let footer = align(position, 4);
+        let footer_len = sir0_footer_len(&sir0_pointers);
+        let total_len = align(footer + footer_len, options.final_alignment as u64);
+
+        LayoutPlan {
+            content_data,
+            entry_pointer_list,
+            entries,
+            strings,
+            footer,
+            footer_len,
+            total_len,
+            sir0_pointers,
+            order,
+            flag_blocks,
+            footer_data: Vec::new(),
+        }
+    }
+
+    /// Remove the entries identical to a previous one, and compare the size of the file written with the default
+    /// options before that to the one written with [`WriteOptions::compact`] after.
+    ///
+    /// This doesn't deduplicate the flag blocks by itself: the list must then be written with
+    /// [`WriteOptions::compact`] to get `compact_len`.
+    pub fn compact(&mut self) -> CompactReport {
+        let original_len = self.plan_layout().total_len;
+        let original_count = self.entries.len();
+        let mut seen = HashSet::new();
+        let keep: Vec<bool> = self.entries.iter().map(|entry| seen.insert(entry)).collect();
+        let mut keep = keep.into_iter();
+        self.entries.retain(|_| keep.next().unwrap_or(false));
+        CompactReport {
+            removed_entries: original_count - self.entries.len(),
+            original_len,
+            compact_len: self.plan_layout_unchecked(&WriteOptions::compact()).total_len,
+        }
+    }
+
+    /// Return the index of every entry, in the order they would be written
+    fn entry_order(&self, entry_order: EntryOrder) -> Vec<usize> {
+        let mut order: Vec<usize> = (0..self.entries.len()).collect();
+        match entry_order {
+            EntryOrder::AsIs => (),
+            EntryOrder::SortedByName => order.sort_by(|a, b| self.entries[*a].entity_name.cmp(&self.entries[*b].entity_name)),
+        };
+        order
+    }
+}
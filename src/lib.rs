@@ -1,2 +1,43 @@
+mod block;
+
+mod glob;
+
+mod crc32;
+
 mod script_entry_list;
-pub use script_entry_list::{ScriptEntryList, ScriptEntryListError, ScriptEntry};
+pub use script_entry_list::{ScriptEntryList, ScriptEntryListError, ScriptEntry, LuaPath, PlbPath, WriteOptions, EntryOrder, StringOrder, ParseOptions, ParseWarning, DedupSavings, flag_diff};
+
+mod layout;
+pub use layout::{LayoutPlan, EntryLayout, StringLayout, AnnotatedEntry, AnnotatedFile, CompactReport};
+
+mod field_patch;
+pub use field_patch::{EntryField, FieldPatchOutcome};
+
+mod string_codec;
+pub use string_codec::{StringCodec, Utf8Codec, Utf16LeCodec, FieldCodecs};
+
+mod footer;
+pub use footer::{FooterWriter, Sir0Footer};
+
+mod patch;
+pub use patch::Patch;
+
+mod lint;
+pub use lint::{PathField, MissingFile, FlagLint, StringField, LengthWarning, SeparatorStyle, SeparatorReport};
+
+mod incremental;
+
+mod parallel;
+
+mod directory;
+
+mod borrowed;
+pub use borrowed::ScriptEntryRef;
+
+mod reader;
+pub use reader::ScriptEntryListReader;
+
+mod codec;
+pub use codec::ScriptEntryListCodec;
+
+pub mod flags;
@@ -0,0 +1,171 @@
+use crate::{FieldCodecs, ScriptEntryList};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// One of the two path fields of an entry.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PathField {
+    Lua,
+    Plb,
+}
+
+/// A path of an entry that doesn't point to an existing file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MissingFile {
+    pub entry_index: usize,
+    pub field: PathField,
+    /// the path, resolved relative to the root
+    pub path: PathBuf,
+}
+
+/// A flag bit set outside of the mask given to [`ScriptEntryList::lint_flags`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct FlagLint {
+    pub entry_index: usize,
+    /// the index of the flag word, from 0 to 3
+    pub word: usize,
+    pub bit: u8,
+}
+
+/// One of the four string fields of an entry.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum StringField {
+    EntityName,
+    MapName,
+    LuaPath,
+    PlbPath,
+}
+
+/// A string longer than the limit given to [`ScriptEntryList::lint_string_lengths`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct LengthWarning {
+    pub entry_index: usize,
+    pub field: StringField,
+    /// the length of the string in bytes, as encoded by the codec of its field, without the NUL terminator
+    pub len: usize,
+}
+
+/// The path separators found in one of the path fields, across all entries.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SeparatorStyle {
+    /// No path contains a separator
+    None,
+    /// Only `/`
+    ForwardSlash,
+    /// Only `\`
+    Backslash,
+    /// Both `/` and `\`, possibly in different paths
+    Mixed,
+}
+
+impl SeparatorStyle {
+    fn of_paths<'a, I: Iterator<Item = &'a str>>(paths: I) -> SeparatorStyle {
+        let (mut forward, mut backward) = (false, false);
+        for path in paths {
+            forward |= path.contains('/');
+            backward |= path.contains('\\');
+        }
+        match (forward, backward) {
+            (false, false) => SeparatorStyle::None,
+            (true, false) => SeparatorStyle::ForwardSlash,
+            (false, true) => SeparatorStyle::Backslash,
+            (true, true) => SeparatorStyle::Mixed,
+        }
+    }
+}
+
+/// The separator style of the lua and plb paths, as returned by [`ScriptEntryList::separator_style`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SeparatorReport {
+    pub lua: SeparatorStyle,
+    pub plb: SeparatorStyle,
+}
+
+impl ScriptEntryList {
+    /// Check every lua and plb path, resolved relative to `root`, point to an existing file. Empty paths are ignored.
+    pub fn validate_files_exist(&self, root: &Path) -> Vec<MissingFile> {
+        let mut missing = Vec::new();
+        for (entry_index, entry) in self.entries.iter().enumerate() {
+            for (field, path) in [(PathField::Lua, &entry.lua_path), (PathField::Plb, &entry.plb_path)] {
+                if path.is_empty() {
+                    continue;
+                };
+                let path = root.join(path);
+                if !path.is_file() {
+                    missing.push(MissingFile {
+                        entry_index,
+                        field,
+                        path,
+                    });
+                };
+            }
+        }
+        missing
+    }
+
+    /// Report every flag bit set in an entry but not in `known_good`, the mask of the bits expected to be used
+    /// (for example, the ones observed set in the official files). Results are sorted by entry, word then bit.
+    pub fn lint_flags(&self, known_good: [u32; 4]) -> Vec<FlagLint> {
+        let mut lints = Vec::new();
+        for (entry_index, entry) in self.entries.iter().enumerate() {
+            for (word, (flag, mask)) in entry.flags.iter().zip(&known_good).enumerate() {
+                let unexpected = flag & !mask;
+                for bit in 0..32 {
+                    if unexpected & (1 << bit) != 0 {
+                        lints.push(FlagLint {
+                            entry_index,
+                            word,
+                            bit,
+                        });
+                    };
+                }
+            }
+        }
+        lints
+    }
+
+    /// Report every string longer than the limit of its field in `limits`, once encoded with the codec of that field
+    /// in `codecs`. The limits are in bytes, without the NUL terminator. Fields without a limit aren't checked.
+    pub fn lint_string_lengths(&self, limits: &HashMap<StringField, usize>, codecs: &FieldCodecs) -> Vec<LengthWarning> {
+        let mut warnings = Vec::new();
+        for (entry_index, entry) in self.entries.iter().enumerate() {
+            for (field, codec, string) in [
+                (StringField::EntityName, &codecs.entity_name, &entry.entity_name),
+                (StringField::MapName, &codecs.map_name, &entry.map_name),
+                (StringField::LuaPath, &codecs.lua_path, &entry.lua_path),
+                (StringField::PlbPath, &codecs.plb_path, &entry.plb_path),
+            ] {
+                let max_len = match limits.get(&field) {
+                    Some(max_len) => *max_len,
+                    None => continue,
+                };
+                let len = codec.encode(string).len();
+                if len > max_len {
+                    warnings.push(LengthWarning {
+                        entry_index,
+                        field,
+                        len,
+                    });
+                };
+            }
+        }
+        warnings
+    }
+
+    /// Return the index of every entry whose entity name or map name is empty or only made of whitespace.
+    pub fn find_empty_names(&self) -> Vec<usize> {
+        self.entries.iter()
+            .enumerate()
+            .filter(|(_, entry)| entry.entity_name.trim().is_empty() || entry.map_name.trim().is_empty())
+            .map(|(index, _)| index)
+            .collect()
+    }
+
+    /// Report which path separators are used by the lua paths and by the plb paths.
+    pub fn separator_style(&self) -> SeparatorReport {
+        SeparatorReport {
+            lua: SeparatorStyle::of_paths(self.lua_paths()),
+            plb: SeparatorStyle::of_paths(self.plb_paths()),
+        }
+    }
+}
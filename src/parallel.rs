@@ -0,0 +1,57 @@
+use crate::{ParseOptions, ScriptEntry, ScriptEntryList, ScriptEntryListError};
+use crate::script_entry_list::{read_content_header, read_entry, read_u32};
+use std::io::{Cursor, Seek, SeekFrom};
+use std::thread;
+
+impl ScriptEntryList {
+    /// Parse an entry list file already in memory, decoding the entries on up to `threads` threads, each with its
+    /// own cursor over `buffer`.
+    ///
+    /// The result is the same as [`ScriptEntryList::new_from_file`]. If multiple entries fail to parse, the error
+    /// of the first one is returned.
+    pub fn new_from_slice_parallel(buffer: &[u8], threads: usize) -> Result<ScriptEntryList, ScriptEntryListError> {
+        ScriptEntryList::new_from_slice_parallel_with_options(buffer, threads, &ParseOptions::default())
+    }
+
+    /// Same as [`ScriptEntryList::new_from_slice_parallel`], with the given options. Entry count related options
+    /// are ignored, and in strict mode only misaligned strings are an error.
+    pub fn new_from_slice_parallel_with_options(buffer: &[u8], threads: usize, options: &ParseOptions) -> Result<ScriptEntryList, ScriptEntryListError> {
+        let mut cursor = Cursor::new(buffer);
+        let header = read_content_header(&mut cursor, &options.magic)?;
+        cursor.seek(SeekFrom::Start(header.pointer_entry_list as u64))?;
+        let mut pointer_entry_list = Vec::new();
+        for _ in 0..header.entry_count {
+            pointer_entry_list.push(read_u32(&mut cursor)? as u64);
+        }
+        if pointer_entry_list.is_empty() {
+            return Ok(ScriptEntryList::from_entries(Vec::new()));
+        };
+
+        let file_len = header.file_len;
+        let chunk_len = (pointer_entry_list.len() + threads.max(1) - 1) / threads.max(1);
+        let chunks: Vec<Result<Vec<ScriptEntry>, ScriptEntryListError>> = thread::scope(|scope| {
+            let handles: Vec<_> = pointer_entry_list.chunks(chunk_len)
+                .enumerate()
+                .map(|(chunk_id, chunk)| {
+                    scope.spawn(move || {
+                        let mut cursor = Cursor::new(buffer);
+                        chunk.iter()
+                            .enumerate()
+                            .map(|(id, pointer_entry)| {
+                                let index = chunk_id * chunk_len + id;
+                                Ok(read_entry(&mut cursor, index, *pointer_entry, file_len, options, &mut Vec::new())?.0)
+                            })
+                            .collect()
+                    })
+                })
+                .collect();
+            handles.into_iter().map(|handle| handle.join().unwrap()).collect()
+        });
+
+        let mut entries = Vec::with_capacity(pointer_entry_list.len());
+        for chunk in chunks {
+            entries.extend(chunk?);
+        }
+        Ok(ScriptEntryList::from_entries(entries))
+    }
+}
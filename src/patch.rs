@@ -0,0 +1,72 @@
+use crate::{ScriptEntry, ScriptEntryList};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// The differences between two [`ScriptEntryList`], keyed by entity name.
+///
+/// Entity names are assumed to be unique. When they aren't, only the first entry with a given name is considered.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Patch {
+    /// entries whose entity name wasn't in the base list
+    pub added: Vec<ScriptEntry>,
+    /// entity names of the entries that aren't in the new list
+    pub removed: Vec<String>,
+    /// the new value of entries that changed
+    pub modified: Vec<ScriptEntry>,
+}
+
+impl Patch {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.modified.is_empty()
+    }
+}
+
+/// Index the first entry with each entity name
+fn by_entity_name(list: &ScriptEntryList) -> HashMap<&str, &ScriptEntry> {
+    let mut result = HashMap::new();
+    for entry in &list.entries {
+        result.entry(entry.entity_name.as_str()).or_insert(entry);
+    }
+    result
+}
+
+impl ScriptEntryList {
+    /// Compute the patch that turns this list into `other`.
+    pub fn make_patch(&self, other: &ScriptEntryList) -> Patch {
+        let base = by_entity_name(self);
+        let new = by_entity_name(other);
+        let mut patch = Patch::default();
+        for entry in &other.entries {
+            if !std::ptr::eq(new[entry.entity_name.as_str()], entry) {
+                // not the first entry with this name
+                continue;
+            };
+            match base.get(entry.entity_name.as_str()) {
+                None => patch.added.push(entry.clone()),
+                Some(base_entry) if *base_entry != entry => patch.modified.push(entry.clone()),
+                Some(_) => (),
+            };
+        }
+        for entry in &self.entries {
+            if !new.contains_key(entry.entity_name.as_str()) && !patch.removed.contains(&entry.entity_name) {
+                patch.removed.push(entry.entity_name.clone());
+            }
+        }
+        patch
+    }
+
+    /// Apply a patch made by [`ScriptEntryList::make_patch`].
+    ///
+    /// Every entry with a removed entity name is removed, the first entry with a modified entity name is replaced,
+    /// and added entries are appended at the end.
+    pub fn apply_patch(&mut self, patch: &Patch) {
+        self.entries.retain(|entry| !patch.removed.contains(&entry.entity_name));
+        for modified in &patch.modified {
+            match self.entries.iter_mut().find(|entry| entry.entity_name == modified.entity_name) {
+                Some(entry) => *entry = modified.clone(),
+                None => self.entries.push(modified.clone()),
+            };
+        }
+        self.entries.extend(patch.added.iter().cloned());
+    }
+}
@@ -0,0 +1,56 @@
+use crate::{ParseOptions, ScriptEntry, ScriptEntryListError};
+use crate::script_entry_list::{read_content_header, read_entry, read_u32};
+use std::io::{Read, Seek, SeekFrom};
+
+/// Random access to the entries of an entry list file, decoding them only when asked for.
+///
+/// Only the entry pointer list is kept in memory.
+#[derive(Debug)]
+pub struct ScriptEntryListReader<F: Read + Seek> {
+    file: F,
+    pointer_entry_list: Vec<u64>,
+    file_len: u64,
+    options: ParseOptions,
+}
+
+impl<F: Read + Seek> ScriptEntryListReader<F> {
+    /// Read the header and the entry pointer list of `file`.
+    pub fn new(file: F) -> Result<ScriptEntryListReader<F>, ScriptEntryListError> {
+        ScriptEntryListReader::new_with_options(file, ParseOptions::default())
+    }
+
+    /// Same as [`ScriptEntryListReader::new`], decoding the strings as the options say. Only the string related
+    /// options and the magic are used.
+    pub fn new_with_options(mut file: F, options: ParseOptions) -> Result<ScriptEntryListReader<F>, ScriptEntryListError> {
+        let header = read_content_header(&mut file, &options.magic)?;
+        file.seek(SeekFrom::Start(header.pointer_entry_list as u64))?;
+        let mut pointer_entry_list = Vec::new();
+        for _ in 0..header.entry_count {
+            pointer_entry_list.push(read_u32(&mut file)? as u64);
+        }
+        Ok(ScriptEntryListReader {
+            file,
+            pointer_entry_list,
+            file_len: header.file_len,
+            options,
+        })
+    }
+
+    pub fn len(&self) -> usize {
+        self.pointer_entry_list.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pointer_entry_list.is_empty()
+    }
+
+    /// Decode the entry at `index`.
+    pub fn get(&mut self, index: usize) -> Result<ScriptEntry, ScriptEntryListError> {
+        let pointer_entry = *self.pointer_entry_list.get(index).ok_or(ScriptEntryListError::InvalidEntryIndex(index))?;
+        Ok(read_entry(&mut self.file, index, pointer_entry, self.file_len, &self.options, &mut Vec::new())?.0)
+    }
+
+    pub fn into_inner(self) -> F {
+        self.file
+    }
+}
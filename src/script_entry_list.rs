@@ -1,15 +1,65 @@
 use serde::{Deserialize, Serialize};
-use std::{io, io::{Read, Write, Seek, SeekFrom}};
+use std::{io, io::{Cursor, Read, Write, Seek, SeekFrom}};
 use std::string::{FromUtf8Error, FromUtf16Error};
-use std::collections::{HashSet, HashMap};
-use pmd_sir0::write_sir0_footer;
+use std::collections::{BTreeMap, HashSet, HashMap};
+use std::fmt;
+use std::sync::Arc;
+use pmd_sir0::Sir0WriteFooterError;
+use crate::{EntryLayout, FooterWriter, Sir0Footer};
+use crate::block::Block;
+use crate::glob::glob_match;
+use crate::crc32::crc32;
+use crate::string_codec::{read_referenced_bytes, read_referenced_string, FieldCodecs, StringCodec};
 
 #[derive(Debug)]
 pub enum ScriptEntryListError {
     IOError(io::Error),
+    /// The file doesn't start with the expected magic. Contains the magic actually found
     InvalidHeader([u8; 4]),
     FromUtf8Error(FromUtf8Error),
     FromUtf16Error(FromUtf16Error),
+    Sir0(Sir0WriteFooterError),
+    InvalidAlignment(usize),
+    /// The input is too short to even contain the SIR0 header (16 bytes)
+    TooSmall { len: u64 },
+    /// The string can't be written such as it read back identically (it contain a NUL character, or can't be
+    /// represented in the encoding of its field)
+    UnencodableString(String),
+    /// The written file didn't parse back to the list that was written
+    VerificationFailed,
+    /// No NUL terminator was found for the string at this offset before the maximum string length
+    UnterminatedString { offset: u64 },
+    /// The columns given to [`ScriptEntryList::from_columns`] (or [`ScriptEntryList::set_flag_table`]) don't all
+    /// have the same length
+    ColumnLengthMismatch { expected: usize, found: usize },
+    /// There is no entry at this index
+    InvalidEntryIndex(usize),
+    /// [`WriteOptions::content_header_len`] is less than 8 or not a multiple of 4
+    InvalidContentHeaderLen(usize),
+    /// No entry has this entity name
+    EntityNotFound(String),
+    /// An entry already has this entity name
+    EntityNameTaken(String),
+    /// The record of the entry at `index` starts at `offset`, too close to the end of the file to hold its 20 bytes
+    TruncatedEntryRecord { index: usize, offset: u64 },
+    /// A string with a code unit larger than a byte (like utf-16) doesn't start on a multiple of its code unit
+    /// size, or has an odd trailing byte before its terminator. Only an error in strict mode, or if the string then
+    /// can't be decoded
+    MisalignedUtf16 { offset: u64 },
+    /// A problem that would otherwise be reported as a warning was found while parsing with
+    /// [`ParseOptions::strict`]
+    Strict(ParseWarning),
+    /// A pointer of the original file given to an incremental write points at `offset`, past the end of the `len`
+    /// bytes it can point into
+    OutOfBounds { offset: u64, len: u64 },
+    /// The footer of the original file given to an incremental write starts at `footer`, before the end of the data
+    /// it should follow, at `data_end`
+    FooterInsideData { footer: u64, data_end: u64 },
+    /// The bytes given to [`crate::Utf16LeCodec`] to decode have this odd length, so the last code unit is incomplete
+    OddUtf16Length(usize),
+    /// No null pointer ends the entry pointer list starting at this offset, read with
+    /// [`ParseOptions::pointer_list_sentinel`], before the footer, the content data header or the end of the file
+    UnterminatedPointerList { offset: u64 },
 }
 
 impl From<io::Error> for ScriptEntryListError {
@@ -30,48 +80,280 @@ impl From<FromUtf16Error> for ScriptEntryListError {
     }
 }
 
+impl From<Sir0WriteFooterError> for ScriptEntryListError {
+    fn from(err: Sir0WriteFooterError) -> ScriptEntryListError {
+        ScriptEntryListError::Sir0(err)
+    }
+}
+
 pub fn read_u32<F: Read>(file: &mut F) -> Result<u32, ScriptEntryListError> {
     let mut buffer = [0; 4];
     file.read_exact(&mut buffer)?;
     Ok(u32::from_le_bytes(buffer))
 }
 
-pub fn read_referenced_utf8_string<F: Read + Seek>(file: &mut F, reference: u64) -> Result<String, ScriptEntryListError> {
-    file.seek(SeekFrom::Start(reference))?;
-    let mut result = String::new();
-    let mut buffer = [0];
-    loop {
-        file.read_exact(&mut buffer)?;
-        if buffer == [0] {
-            return Ok(result)
+/// The magic of a standard entry list file
+pub(crate) const SIR0_MAGIC: [u8; 4] = *b"SIR0";
+
+/// Return the offset of the first `magic` found between `start` and `end`, if any
+fn find_magic<F: Read + Seek>(file: &mut F, magic: &[u8; 4], start: u64, end: u64) -> Result<Option<u64>, ScriptEntryListError> {
+    let mut chunk_start = start;
+    let mut chunk = Vec::new();
+    while chunk_start + 4 <= end {
+        // chunks overlap by 3 bytes, so a magic split between two chunks is still found
+        file.seek(SeekFrom::Start(chunk_start))?;
+        chunk.clear();
+        file.by_ref().take((end - chunk_start).min(4096)).read_to_end(&mut chunk)?;
+        if let Some(position) = chunk.windows(4).position(|window| window == magic) {
+            return Ok(Some(chunk_start + position as u64));
+        };
+        if chunk.len() < 4 {
+            break;
         };
-        result.push_str(&String::from_utf8(buffer.to_vec())?)
+        chunk_start += chunk.len() as u64 - 3;
+    };
+    Ok(None)
+}
+
+/// Return the position of the cursor relative to `base`
+fn relative_position<F: Seek>(file: &mut F, base: u64) -> Result<u64, io::Error> {
+    Ok(file.stream_position()? - base)
+}
+
+pub(crate) struct ContentHeader {
+    pub(crate) file_len: u64,
+    pub(crate) pointer_content_data: u32,
+    pub(crate) pointer_footer: u32,
+    pub(crate) entry_count: u32,
+    pub(crate) pointer_entry_list: u32,
+}
+
+impl ContentHeader {
+    /// The offset the entry pointer list can't extend past: the first of the footer and the content data header
+    /// after its start, or the end of the file
+    pub(crate) fn pointer_list_bound(&self) -> u64 {
+        [self.pointer_footer, self.pointer_content_data].iter()
+            .map(|offset| *offset as u64)
+            .filter(|offset| *offset > self.pointer_entry_list as u64)
+            .fold(self.file_len, u64::min)
     }
 }
 
-pub fn read_referenced_utf16_string<F: Read + Seek>(file: &mut F, reference: u64) -> Result<String, ScriptEntryListError> {
-    file.seek(SeekFrom::Start(reference))?;
-    let mut result = String::new();
-    let mut buffer = [0; 2];
+/// Read the SIR0 header (checking it starts with `magic`) and the content data header
+pub(crate) fn read_content_header<F: Read + Seek>(file: &mut F, magic: &[u8; 4]) -> Result<ContentHeader, ScriptEntryListError> {
+    let len = file.seek(SeekFrom::End(0))?;
+    if len < 16 {
+        return Err(ScriptEntryListError::TooSmall { len });
+    };
+
+    file.seek(SeekFrom::Start(0))?;
+    let mut header_buf = [0; 4];
+    file.read_exact(&mut header_buf)?;
+    if &header_buf != magic {
+        return Err(ScriptEntryListError::InvalidHeader(header_buf));
+    };
+
+    let pointer_content_data = read_u32(file)?;
+    let pointer_footer = read_u32(file)?;
+
+    file.seek(SeekFrom::Start(pointer_content_data as u64))?;
+    let entry_count = read_u32(file)?;
+    let pointer_entry_list = read_u32(file)?;
+    Ok(ContentHeader {
+        file_len: len,
+        pointer_content_data,
+        pointer_footer,
+        entry_count,
+        pointer_entry_list,
+    })
+}
+
+/// Guess the number of element in the entry pointer list, assuming it is directly followed by the entries themselves
+/// (like the official files and this crate do).
+fn infer_pointer_list_len<F: Read + Seek>(file: &mut F, header: &ContentHeader) -> Result<u32, ScriptEntryListError> {
+    let list_start = header.pointer_entry_list as u64;
+    file.seek(SeekFrom::Start(list_start))?;
+    let mut list_end = header.pointer_list_bound();
+    let mut count = 0;
     loop {
-        file.read_exact(&mut buffer)?;
-        let charid = u16::from_le_bytes(buffer);
-        if charid == 0 {
-            return Ok(result)
+        let position = list_start + count as u64 * 4;
+        if position + 4 > list_end {
+            break;
+        };
+        let pointer = read_u32(file)? as u64;
+        // an entry is 20 bytes long, and can't be in the pointer list itself
+        if pointer < position + 4 || pointer + 20 > header.file_len {
+            break;
+        };
+        list_end = list_end.min(pointer);
+        count += 1;
+    };
+    Ok(count)
+}
+
+/// Whether `encoded`, a string read by steps of `unit_size` bytes, contains a NUL code unit that isn't aligned to
+/// `unit_size`. This is the sign of a string with an odd trailing byte before its terminator, whose read continued
+/// past it.
+fn has_misaligned_terminator(encoded: &[u8], unit_size: usize) -> bool {
+    unit_size > 1 && encoded.windows(unit_size)
+        .enumerate()
+        .any(|(position, window)| position % unit_size != 0 && window.iter().all(|byte| *byte == 0))
+}
+
+/// Read a string of an entry, first checking `reference` is aligned to the code unit size, and that the string
+/// doesn't have an odd trailing byte before its terminator. Such a misaligned string is an error in strict mode,
+/// otherwise it is reported and read anyway. If it then can't be decoded, the misalignment is returned as the
+/// error, as it is the likely cause.
+fn read_entry_string<F: Read + Seek>(file: &mut F, reference: u64, codec: &dyn StringCodec, options: &ParseOptions, warnings: &mut Vec<ParseWarning>) -> Result<String, ScriptEntryListError> {
+    let unit_size = codec.unit_size();
+    if reference % unit_size as u64 == 0 {
+        let encoded = read_referenced_bytes(file, reference, unit_size, options.max_string_len)?;
+        if !has_misaligned_terminator(&encoded, unit_size) {
+            return codec.decode(&encoded);
         };
-        result.push_str(&String::from_utf16(&[charid])?)
+    };
+    if options.strict {
+        return Err(ScriptEntryListError::MisalignedUtf16 { offset: reference });
+    };
+    warnings.push(ParseWarning::MisalignedUtf16 { offset: reference });
+    match read_referenced_string(file, reference, codec, options.max_string_len) {
+        Err(ScriptEntryListError::FromUtf8Error(_)) | Err(ScriptEntryListError::FromUtf16Error(_)) => Err(ScriptEntryListError::MisalignedUtf16 { offset: reference }),
+        result => result,
     }
 }
 
-pub fn string_to_utf16(string: &str) -> Vec<u8> {
-    let mut result = Vec::new();
-    for chara in string.encode_utf16() {
-        result.extend_from_slice(&u16::to_le_bytes(chara))
+/// Parse the entry `index`, whose record is at `pointer_entry`, also returning where its parts were found.
+/// Misaligned strings are reported to `warnings`.
+pub(crate) fn read_entry<F: Read + Seek>(file: &mut F, index: usize, pointer_entry: u64, file_len: u64, options: &ParseOptions, warnings: &mut Vec<ParseWarning>) -> Result<(ScriptEntry, EntryLayout), ScriptEntryListError> {
+    if pointer_entry.saturating_add(20) > file_len {
+        return Err(ScriptEntryListError::TruncatedEntryRecord { index, offset: pointer_entry });
     };
-    result
+    file.seek(SeekFrom::Start(pointer_entry))?;
+
+    let actual_entity_name_pointer = read_u32(file)? as u64;
+    let actual_map_name_pointer = read_u32(file)? as u64;
+    let actual_lua_path_pointer = read_u32(file)? as u64;
+    let actual_plb_path_pointer = read_u32(file)? as u64;
+    let actual_flags_pointer = read_u32(file)? as u64;
+
+    let codecs = &options.codecs;
+    let entity_name = read_entry_string(file, actual_entity_name_pointer, &*codecs.entity_name, options, warnings)?;
+    let map_name = read_entry_string(file, actual_map_name_pointer, &*codecs.map_name, options, warnings)?;
+    let lua_path = read_entry_string(file, actual_lua_path_pointer, &*codecs.lua_path, options, warnings)?;
+    let plb_path = read_entry_string(file, actual_plb_path_pointer, &*codecs.plb_path, options, warnings)?;
+
+    file.seek(SeekFrom::Start(actual_flags_pointer))?;
+    let mut flags = [0; 4];
+    #[allow(clippy::needless_range_loop)]
+    for flag_id in 0..4 {
+        flags[flag_id] = read_u32(file)?;
+    };
+
+    Ok((ScriptEntry {
+        entity_name,
+        map_name,
+        lua_path,
+        plb_path,
+        flags,
+    }, EntryLayout {
+        record: pointer_entry,
+        entity_name: actual_entity_name_pointer,
+        map_name: actual_map_name_pointer,
+        lua_path: actual_lua_path_pointer,
+        plb_path: actual_plb_path_pointer,
+        flags: actual_flags_pointer,
+    }))
 }
 
-#[derive(Serialize, Deserialize)]
+/// Parse the entries of the file, appending them to `entries` and where they were found to `layouts`
+pub(crate) fn read_entries_into<F: Read + Seek>(file: &mut F, entries: &mut Vec<ScriptEntry>, layouts: &mut Vec<EntryLayout>, options: &ParseOptions, warnings: &mut Vec<ParseWarning>) -> Result<usize, ScriptEntryListError> {
+    let header = read_content_header(file, &options.magic)?;
+    if header.pointer_content_data != 16 {
+        report(ParseWarning::UnexpectedContentDataPointer(header.pointer_content_data), options, warnings)?;
+    };
+
+    let all_pointer_entry = if options.pointer_list_sentinel {
+        file.seek(SeekFrom::Start(header.pointer_entry_list as u64))?;
+        let list_end = header.pointer_list_bound();
+        let mut all_pointer_entry = Vec::new();
+        loop {
+            if header.pointer_entry_list as u64 + (all_pointer_entry.len() as u64 + 1) * 4 > list_end {
+                return Err(ScriptEntryListError::UnterminatedPointerList { offset: header.pointer_entry_list as u64 });
+            };
+            let pointer = read_u32(file)? as u64;
+            if pointer == 0 {
+                break;
+            };
+            all_pointer_entry.push(pointer);
+        };
+        if all_pointer_entry.len() != header.entry_count as usize {
+            warnings.push(ParseWarning::EntryCountMismatch {
+                header: header.entry_count,
+                pointer_list: all_pointer_entry.len() as u32,
+            });
+        };
+        all_pointer_entry
+    } else {
+        let mut entry_count = header.entry_count;
+        if options.repair_entry_count {
+            let pointer_list_len = infer_pointer_list_len(file, &header)?;
+            if pointer_list_len != entry_count {
+                warnings.push(ParseWarning::EntryCountMismatch {
+                    header: entry_count,
+                    pointer_list: pointer_list_len,
+                });
+                entry_count = pointer_list_len;
+            };
+        };
+
+        file.seek(SeekFrom::Start(header.pointer_entry_list as u64))?;
+        let mut all_pointer_entry = Vec::new();
+        for _ in 0..entry_count {
+            all_pointer_entry.push(read_u32(file)? as u64);
+        };
+        all_pointer_entry
+    };
+
+    let start_len = entries.len();
+    let layouts_start_len = layouts.len();
+    let pointer_list_end = header.pointer_entry_list as u64 + all_pointer_entry.len() as u64 * 4;
+    for (index, pointer_entry) in all_pointer_entry.into_iter().enumerate() {
+        let (entry, layout) = read_entry(file, index, pointer_entry, header.file_len, options, warnings)?;
+        entries.push(entry);
+        layouts.push(layout);
+    };
+
+    // check the bytes between the end of the data and the footer are zero padding
+    let codecs = &options.codecs;
+    // the content data header may have been moved after the entries
+    let mut data_end = pointer_list_end.max(header.pointer_content_data as u64 + 8);
+    for (entry, layout) in entries[start_len..].iter().zip(&layouts[layouts_start_len..]) {
+        data_end = data_end.max(layout.record + 20).max(layout.flags + 16);
+        for (string, pointer, codec) in [
+            (&entry.entity_name, layout.entity_name, &codecs.entity_name),
+            (&entry.map_name, layout.map_name, &codecs.map_name),
+            (&entry.lua_path, layout.lua_path, &codecs.lua_path),
+            (&entry.plb_path, layout.plb_path, &codecs.plb_path),
+        ] {
+            if pointer != 0 {
+                data_end = data_end.max(pointer + (codec.encode(string).len() + codec.unit_size()) as u64);
+            };
+        }
+    };
+    let footer = (header.pointer_footer as u64).min(header.file_len);
+    if data_end < footer {
+        file.seek(SeekFrom::Start(data_end))?;
+        let mut padding = Vec::new();
+        file.by_ref().take(footer - data_end).read_to_end(&mut padding)?;
+        if let Some(position) = padding.iter().position(|byte| *byte != 0) {
+            report(ParseWarning::NonZeroPadding { offset: data_end + position as u64 }, options, warnings)?;
+        };
+    };
+
+    Ok(entries.len() - start_len)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct ScriptEntry {
     pub entity_name: String,
     pub map_name: String,
@@ -80,191 +362,1005 @@ pub struct ScriptEntry {
     pub flags: [u32; 4],
 }
 
-#[derive(Serialize, Deserialize)]
+/// Count the strings appearing more than once
+fn shared_strings<'a, I: Iterator<Item = &'a str>>(strings: I) -> Vec<(&'a str, usize)> {
+    let mut count: BTreeMap<&str, usize> = BTreeMap::new();
+    for string in strings {
+        *count.entry(string).or_insert(0) += 1;
+    };
+    count.into_iter().filter(|(_, count)| *count > 1).collect()
+}
+
+/// Convert to `(entity_name, map_name, lua_path, plb_path, flags)`
+impl From<ScriptEntry> for (String, String, String, String, [u32; 4]) {
+    fn from(entry: ScriptEntry) -> (String, String, String, String, [u32; 4]) {
+        (entry.entity_name, entry.map_name, entry.lua_path, entry.plb_path, entry.flags)
+    }
+}
+
+/// Convert from `(entity_name, map_name, lua_path, plb_path, flags)`
+impl From<(String, String, String, String, [u32; 4])> for ScriptEntry {
+    fn from((entity_name, map_name, lua_path, plb_path, flags): (String, String, String, String, [u32; 4])) -> ScriptEntry {
+        ScriptEntry {
+            entity_name,
+            map_name,
+            lua_path,
+            plb_path,
+            flags,
+        }
+    }
+}
+
+/// Return the (word, bit) positions where the flags of the two entries differ, in increasing order.
+pub fn flag_diff(a: &ScriptEntry, b: &ScriptEntry) -> Vec<(usize, u8)> {
+    let mut result = Vec::new();
+    for (word, (a_word, b_word)) in a.flags.iter().zip(b.flags.iter()).enumerate() {
+        let diff = a_word ^ b_word;
+        for bit in 0..32 {
+            if diff & (1 << bit) != 0 {
+                result.push((word, bit));
+            }
+        }
+    }
+    result
+}
+
+/// Options controlling how a [`ScriptEntryList`] is written.
+#[derive(Debug, Clone)]
+pub struct WriteOptions {
+    /// The total output (including the SIR0 footer) is padded with zero to a multiple of this. Must be a power of two.
+    pub final_alignment: usize,
+    /// Write a null pointer after the entry pointer list, for tools that expect the list to be terminated.
+    pub pointer_list_sentinel: bool,
+    pub codecs: FieldCodecs,
+    /// The order entries are written in. Both the entry pointer list and the entries follow it.
+    pub entry_order: EntryOrder,
+    /// The size of the content data header, after which the entry pointer list is written. The first 8 bytes are
+    /// the entry count and the pointer to the entry pointer list, and the remaining ones are written as zero. Must
+    /// be a multiple of 4, and at least 8.
+    ///
+    /// The parser doesn't need to be told about it, as it follow the pointer to the entry pointer list.
+    pub content_header_len: usize,
+    /// Write entries with the exact same flags (same words in the same order) only once, sharing the block.
+    /// Official files don't seem to do so.
+    pub dedup_flags: bool,
+    /// Append the CRC-32 of the whole block (final padding included) after it, to be checked with
+    /// [`ScriptEntryList::verify_crc32`]. This isn't part of the format, but the game ignore the trailing bytes.
+    pub append_crc32: bool,
+    pub string_order: StringOrder,
+    /// How the SIR0 footer is encoded. Default to [`Sir0Footer`].
+    pub footer_writer: Arc<dyn FooterWriter>,
+    /// The magic written at the start of the file. Default to `SIR0`.
+    pub magic: [u8; 4],
+}
+
+impl WriteOptions {
+    /// The options giving the smallest file: flag blocks are deduplicated (strings always are), strings are sorted
+    /// so they need no padding, and there is no final padding.
+    pub fn compact() -> WriteOptions {
+        WriteOptions {
+            dedup_flags: true,
+            ..WriteOptions::default()
+        }
+    }
+}
+
+impl Default for WriteOptions {
+    fn default() -> WriteOptions {
+        WriteOptions {
+            final_alignment: 1,
+            pointer_list_sentinel: false,
+            codecs: FieldCodecs::default(),
+            entry_order: EntryOrder::AsIs,
+            content_header_len: 8,
+            dedup_flags: false,
+            append_crc32: false,
+            string_order: StringOrder::Sorted,
+            footer_writer: Arc::new(Sir0Footer),
+            magic: SIR0_MAGIC,
+        }
+    }
+}
+
+/// The order entries are written in, without changing the order in the [`ScriptEntryList`] itself.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EntryOrder {
+    /// The order of [`ScriptEntryList::entries`]
+    AsIs,
+    /// Sorted by entity name. Entries with the same name keep their relative order
+    SortedByName,
+}
+
+/// The path to a lua script, to not mistake it for a [`PlbPath`] when building an entry.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct LuaPath(pub String);
+
+/// The path to a plb file, to not mistake it for a [`LuaPath`] when building an entry.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct PlbPath(pub String);
+
+impl From<String> for LuaPath {
+    fn from(path: String) -> LuaPath {
+        LuaPath(path)
+    }
+}
+
+impl From<&str> for LuaPath {
+    fn from(path: &str) -> LuaPath {
+        LuaPath(path.to_string())
+    }
+}
+
+impl From<LuaPath> for String {
+    fn from(path: LuaPath) -> String {
+        path.0
+    }
+}
+
+impl From<String> for PlbPath {
+    fn from(path: String) -> PlbPath {
+        PlbPath(path)
+    }
+}
+
+impl From<&str> for PlbPath {
+    fn from(path: &str) -> PlbPath {
+        PlbPath(path.to_string())
+    }
+}
+
+impl From<PlbPath> for String {
+    fn from(path: PlbPath) -> String {
+        path.0
+    }
+}
+
+impl ScriptEntry {
+    /// Build an entry. The paths are typed so they can't be swapped by mistake.
+    pub fn new(entity_name: String, map_name: String, lua_path: LuaPath, plb_path: PlbPath, flags: [u32; 4]) -> ScriptEntry {
+        ScriptEntry {
+            entity_name,
+            map_name,
+            lua_path: lua_path.0,
+            plb_path: plb_path.0,
+            flags,
+        }
+    }
+
+    /// Compare the four string fields only.
+    pub fn eq_ignoring_flags(&self, other: &ScriptEntry) -> bool {
+        self.entity_name == other.entity_name
+            && self.map_name == other.map_name
+            && self.lua_path == other.lua_path
+            && self.plb_path == other.plb_path
+    }
+
+    pub fn set_lua_path(&mut self, path: LuaPath) {
+        self.lua_path = path.0;
+    }
+
+    pub fn set_plb_path(&mut self, path: PlbPath) {
+        self.plb_path = path.0;
+    }
+
+    /// Wrap this entry into a list containing only it, ready to be written.
+    pub fn to_single_entry_list(self) -> ScriptEntryList {
+        ScriptEntryList::from_entries(vec![self])
+    }
+}
+
+/// The order the strings are written in. Either way, the output is deterministic.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StringOrder {
+    /// Strings with the largest code unit first (so they stay aligned without padding), then sorted
+    Sorted,
+    /// In the order they are first referenced by the written entries, which is easier to follow in an hex editor.
+    /// Padding is added as needed to align each string
+    FirstReferenced,
+}
+
+/// Options controlling how an entry list file is parsed.
+#[derive(Debug, Clone)]
+pub struct ParseOptions {
+    /// If the entry count in the header doesn't match the length of the entry pointer list (guessed from where the
+    /// first entry start), trust the latter.
+    /// The discrepancy is reported as a [`ParseWarning::EntryCountMismatch`].
+    pub repair_entry_count: bool,
+    /// The maximum size in bytes of a string, without its NUL terminator, if any. Files written by this crate
+    /// have no limit, so default to `None`.
+    pub max_string_len: Option<usize>,
+    /// Read the entry pointer list until a null pointer, rather than using the entry count of the header.
+    /// A disagreement with the header is reported as a [`ParseWarning::EntryCountMismatch`].
+    pub pointer_list_sentinel: bool,
+    pub codecs: FieldCodecs,
+    /// Fail with [`ScriptEntryListError::Strict`] on layout oddities this crate's writer never produces, instead
+    /// of reporting them as warnings. Entry count mismatches are still handled as described above.
+    pub strict: bool,
+    /// The magic the file is expected to start with. Default to `SIR0`, but some sibling formats are laid out
+    /// the same way with another magic.
+    pub magic: [u8; 4],
+}
+
+impl Default for ParseOptions {
+    fn default() -> ParseOptions {
+        ParseOptions {
+            repair_entry_count: false,
+            max_string_len: None,
+            pointer_list_sentinel: false,
+            codecs: FieldCodecs::default(),
+            strict: false,
+            magic: SIR0_MAGIC,
+        }
+    }
+}
+
+/// A non-fatal problem found while parsing an entry list file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseWarning {
+    /// The header entry count didn't match the entry pointer list length. The latter was used
+    EntryCountMismatch { header: u32, pointer_list: u32 },
+    /// The content data doesn't start right after the SIR0 header, at offset 16
+    UnexpectedContentDataPointer(u32),
+    /// A non-zero byte was found at this offset, between the end of the data and the SIR0 footer
+    NonZeroPadding { offset: u64 },
+    /// A string with a code unit larger than a byte (like utf-16) doesn't start on a multiple of its code unit size,
+    /// or has an odd trailing byte before its terminator, so it may have been misread
+    MisalignedUtf16 { offset: u64 },
+}
+
+/// Record a layout warning, or fail with it in strict mode
+fn report(warning: ParseWarning, options: &ParseOptions, warnings: &mut Vec<ParseWarning>) -> Result<(), ScriptEntryListError> {
+    if options.strict {
+        return Err(ScriptEntryListError::Strict(warning));
+    };
+    warnings.push(warning);
+    Ok(())
+}
+
+/// The number of bytes saved by pooling identical strings when writing, compared to writing each field inline.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct DedupSavings {
+    /// saved on the utf-8 strings (entity and map names), including NUL terminators
+    pub utf8_bytes: usize,
+    /// saved on the utf-16 strings (lua and plb paths), including NUL terminators
+    pub utf16_bytes: usize,
+}
+
+impl DedupSavings {
+    pub fn total(&self) -> usize {
+        self.utf8_bytes + self.utf16_bytes
+    }
+}
+
+#[derive(Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct ScriptEntryList {
     pub entries: Vec<ScriptEntry>,
 }
 
+/// Only show the number of entries, as lists are usually too big to be printed. See [`ScriptEntryList::verbose_debug`].
+impl fmt::Debug for ScriptEntryList {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ScriptEntryList")
+            .field("entries", &self.entries.len())
+            .finish()
+    }
+}
+
+// `Deref<Target = [ScriptEntry]>` is deliberately not implemented: the list isn't a smart pointer, and slice
+// methods would silently take over if an inherent method with the same name is (re)moved.
+impl AsRef<[ScriptEntry]> for ScriptEntryList {
+    fn as_ref(&self) -> &[ScriptEntry] {
+        &self.entries
+    }
+}
+
+impl AsMut<[ScriptEntry]> for ScriptEntryList {
+    fn as_mut(&mut self) -> &mut [ScriptEntry] {
+        &mut self.entries
+    }
+}
+
 impl ScriptEntryList {
-    pub fn new_from_file<F: Read + Seek>(file: &mut F) -> Result<ScriptEntryList, ScriptEntryListError> {
-        file.seek(SeekFrom::Start(0))?;
-        let mut header_buf = [0; 4];
-        file.read_exact(&mut header_buf)?;
-        if &header_buf != b"SIR0" {
-            return Err(ScriptEntryListError::InvalidHeader(header_buf));
+    /// Build a list from one vector per field, the n-th entry being made of the n-th element of each vector.
+    pub fn from_columns(entity_names: Vec<String>, map_names: Vec<String>, lua_paths: Vec<String>, plb_paths: Vec<String>, flags: Vec<[u32; 4]>) -> Result<ScriptEntryList, ScriptEntryListError> {
+        let expected = entity_names.len();
+        for found in &[map_names.len(), lua_paths.len(), plb_paths.len(), flags.len()] {
+            if *found != expected {
+                return Err(ScriptEntryListError::ColumnLengthMismatch { expected, found: *found });
+            };
         };
+        let entries = entity_names.into_iter()
+            .zip(map_names)
+            .zip(lua_paths)
+            .zip(plb_paths)
+            .zip(flags)
+            .map(|((((entity_name, map_name), lua_path), plb_path), flags)| ScriptEntry {
+                entity_name,
+                map_name,
+                lua_path,
+                plb_path,
+                flags,
+            })
+            .collect();
+        Ok(ScriptEntryList::from_entries(entries))
+    }
 
-        let pointer_content_data = read_u32(file)?;
-        let _pointer_pointer_offsets = read_u32(file)?;
+    /// Return the full debug output of every entry.
+    pub fn verbose_debug(&self) -> String {
+        format!("{:#?}", self.entries)
+    }
+
+    pub fn from_entries(entries: Vec<ScriptEntry>) -> ScriptEntryList {
+        ScriptEntryList {
+            entries
+        }
+    }
 
-        file.seek(SeekFrom::Start(pointer_content_data as u64))?;
-        let entry_count = read_u32(file)?;
-        let pointer_entry_list = read_u32(file)?;
+    /// Take the entries out of the list, the reverse of [`ScriptEntryList::from_entries`].
+    pub fn into_entries(self) -> Vec<ScriptEntry> {
+        self.entries
+    }
 
-        file.seek(SeekFrom::Start(pointer_entry_list as u64))?;
+    pub fn from_entry_slice(entries: &[ScriptEntry]) -> ScriptEntryList {
+        ScriptEntryList::from_entries(entries.to_vec())
+    }
+
+    pub fn new_from_file<F: Read + Seek>(file: &mut F) -> Result<ScriptEntryList, ScriptEntryListError> {
+        Ok(ScriptEntryList::new_from_file_with_options(file, &ParseOptions::default())?.0)
+    }
+
+    /// Parse an entry list file, returning it alongside the non-fatal problems found while parsing.
+    pub fn new_from_file_with_options<F: Read + Seek>(file: &mut F, options: &ParseOptions) -> Result<(ScriptEntryList, Vec<ParseWarning>), ScriptEntryListError> {
+        let mut entries = Vec::new();
+        let mut warnings = Vec::new();
+        read_entries_into(file, &mut entries, &mut Vec::new(), options, &mut warnings)?;
+        Ok((ScriptEntryList {
+            entries
+        }, warnings))
+    }
+
+    /// Parse an entry list file, keeping every entry parsed before an error, if any, happened.
+    pub fn new_from_file_partial<F: Read + Seek>(file: &mut F) -> (Vec<ScriptEntry>, Option<ScriptEntryListError>) {
+        let mut entries = Vec::new();
+        let error = read_entries_into(file, &mut entries, &mut Vec::new(), &ParseOptions::default(), &mut Vec::new()).err();
+        (entries, error)
+    }
+
+    /// Parse only the first `max_entries` entries of an entry list file. Also return true if the file has more
+    /// entries than that (according to its header).
+    pub fn new_from_file_limited<F: Read + Seek>(file: &mut F, max_entries: usize) -> Result<(ScriptEntryList, bool), ScriptEntryListError> {
+        ScriptEntryList::new_from_file_limited_with_options(file, max_entries, &ParseOptions::default())
+    }
+
+    /// Same as [`ScriptEntryList::new_from_file_limited`], with the given options. Entry count related options are
+    /// ignored, as is strict mode.
+    pub fn new_from_file_limited_with_options<F: Read + Seek>(file: &mut F, max_entries: usize, options: &ParseOptions) -> Result<(ScriptEntryList, bool), ScriptEntryListError> {
+        let header = read_content_header(file, &options.magic)?;
+        let truncated = header.entry_count as usize > max_entries;
+        let entry_count = (header.entry_count as usize).min(max_entries);
+        file.seek(SeekFrom::Start(header.pointer_entry_list as u64))?;
         let mut all_pointer_entry = Vec::new();
         for _ in 0..entry_count {
             all_pointer_entry.push(read_u32(file)? as u64);
         };
+        let options = ParseOptions {
+            strict: false,
+            ..options.clone()
+        };
+        let mut entries = Vec::new();
+        for (index, pointer_entry) in all_pointer_entry.into_iter().enumerate() {
+            entries.push(read_entry(file, index, pointer_entry, header.file_len, &options, &mut Vec::new())?.0);
+        };
+        Ok((ScriptEntryList::from_entries(entries), truncated))
+    }
 
+    /// Parse an entry list file, also returning where each entry and the data it reference were found in the file.
+    pub fn new_from_file_with_layout<F: Read + Seek>(file: &mut F) -> Result<(ScriptEntryList, Vec<EntryLayout>), ScriptEntryListError> {
         let mut entries = Vec::new();
-        for pointer_entry in all_pointer_entry {
-            file.seek(SeekFrom::Start(pointer_entry))?;
-
-            let actual_entity_name_pointer = read_u32(file)? as u64;
-            let actual_map_name_pointer = read_u32(file)? as u64;
-            let actual_lua_path_pointer = read_u32(file)? as u64;
-            let actual_plb_path_pointer = read_u32(file)? as u64;
-            let actual_flags_pointer = read_u32(file)? as u64;
-
-            let entity_name = read_referenced_utf8_string(file, actual_entity_name_pointer)?;
-            let map_name = read_referenced_utf8_string(file, actual_map_name_pointer)?;
-            let lua_path = read_referenced_utf16_string(file, actual_lua_path_pointer)?;
-            let plb_path = read_referenced_utf16_string(file, actual_plb_path_pointer)?;
-
-            file.seek(SeekFrom::Start(actual_flags_pointer))?;
-            let mut flags = [0; 4];
-            #[allow(clippy::needless_range_loop)]
-            for flag_id in 0..4 {
-                flags[flag_id] = read_u32(file)?;
-            };
+        let mut layouts = Vec::new();
+        read_entries_into(file, &mut entries, &mut layouts, &ParseOptions::default(), &mut Vec::new())?;
+        Ok((ScriptEntryList {
+            entries
+        }, layouts))
+    }
 
-            entries.push(ScriptEntry {
-                entity_name,
-                map_name,
-                lua_path,
-                plb_path,
-                flags,
+    /// Parse every SIR0 block of a file made of multiple entry list files concatenated back to back.
+    ///
+    /// Each block is assumed to end where the next `SIR0` magic after its footer is found (or at the end of the file).
+    pub fn read_all<F: Read + Seek>(file: &mut F) -> Result<Vec<ScriptEntryList>, ScriptEntryListError> {
+        Ok(ScriptEntryList::read_all_with_options(file, &ParseOptions::default())?.0)
+    }
+
+    /// Same as [`ScriptEntryList::read_all`], parsing each block with the given options. Blocks are split on
+    /// [`ParseOptions::magic`], and the warnings of all the blocks are returned together.
+    pub fn read_all_with_options<F: Read + Seek>(file: &mut F, options: &ParseOptions) -> Result<(Vec<ScriptEntryList>, Vec<ParseWarning>), ScriptEntryListError> {
+        let file_len = file.seek(SeekFrom::End(0))?;
+        let mut lists = Vec::new();
+        let mut warnings = Vec::new();
+        let mut base = 0;
+        while base < file_len {
+            file.seek(SeekFrom::Start(base + 8))?;
+            let footer = read_u32(file)? as u64;
+            let end = find_magic(file, &options.magic, base + footer, file_len)?.unwrap_or(file_len);
+            let (list, block_warnings) = ScriptEntryList::new_from_file_with_options(&mut Block::new(file, base, end - base)?, options)?;
+            lists.push(list);
+            warnings.extend(block_warnings);
+            base = end;
+        };
+        Ok((lists, warnings))
+    }
+
+    /// Read only the number of entries stored in the header of an entry list file, without decoding them.
+    ///
+    /// See [`ScriptEntryList::len`] for the number of entries of an in-memory list.
+    pub fn read_entry_count<F: Read + Seek>(file: &mut F) -> Result<u32, ScriptEntryListError> {
+        ScriptEntryList::read_entry_count_with_options(file, &ParseOptions::default())
+    }
+
+    /// Same as [`ScriptEntryList::read_entry_count`], expecting [`ParseOptions::magic`]. Other options are ignored.
+    pub fn read_entry_count_with_options<F: Read + Seek>(file: &mut F, options: &ParseOptions) -> Result<u32, ScriptEntryListError> {
+        Ok(read_content_header(file, &options.magic)?.entry_count)
+    }
+
+    /// Check the entry count in the header of an entry list file match the number of entries actually present,
+    /// as inferred from the entry pointer list (see [`ParseOptions::repair_entry_count`]).
+    pub fn verify_count<F: Read + Seek>(file: &mut F) -> Result<bool, ScriptEntryListError> {
+        ScriptEntryList::verify_count_with_options(file, &ParseOptions::default())
+    }
+
+    /// Same as [`ScriptEntryList::verify_count`], expecting [`ParseOptions::magic`]. Other options are ignored.
+    pub fn verify_count_with_options<F: Read + Seek>(file: &mut F, options: &ParseOptions) -> Result<bool, ScriptEntryListError> {
+        let header = read_content_header(file, &options.magic)?;
+        Ok(infer_pointer_list_len(file, &header)? == header.entry_count)
+    }
+
+    /// Parse another entry list file and append its entries to this list.
+    ///
+    /// Return the number of entries added. If parsing fails, this list is left unchanged.
+    pub fn append_from_file<F: Read + Seek>(&mut self, file: &mut F) -> Result<usize, ScriptEntryListError> {
+        let original_len = self.entries.len();
+        match read_entries_into(file, &mut self.entries, &mut Vec::new(), &ParseOptions::default(), &mut Vec::new()) {
+            Ok(added) => Ok(added),
+            Err(err) => {
+                self.entries.truncate(original_len);
+                Err(err)
+            }
+        }
+    }
+
+    pub fn entity_names(&self) -> impl Iterator<Item = &str> + '_ {
+        self.entries.iter().map(|entry| entry.entity_name.as_str())
+    }
+
+    pub fn map_names(&self) -> impl Iterator<Item = &str> + '_ {
+        self.entries.iter().map(|entry| entry.map_name.as_str())
+    }
+
+    pub fn lua_paths(&self) -> impl Iterator<Item = &str> + '_ {
+        self.entries.iter().map(|entry| entry.lua_path.as_str())
+    }
+
+    pub fn plb_paths(&self) -> impl Iterator<Item = &str> + '_ {
+        self.entries.iter().map(|entry| entry.plb_path.as_str())
+    }
+
+    pub fn flags_iter_mut(&mut self) -> impl Iterator<Item = &mut [u32; 4]> + '_ {
+        self.entries.iter_mut().map(|entry| &mut entry.flags)
+    }
+
+    /// Return the first entry with the given map name and entity name.
+    ///
+    /// Entity names are only assumed to be unique within a map, so both are matched.
+    /// If multiple entries match anyway, the first one is returned.
+    pub fn find_by_map_and_entity(&self, map: &str, entity: &str) -> Option<&ScriptEntry> {
+        self.entries.iter().find(|entry| entry.map_name == map && entry.entity_name == entity)
+    }
+
+    /// Return the first entry with the given lua path.
+    pub fn find_by_lua_path(&self, path: &str) -> Option<&ScriptEntry> {
+        self.entries.iter().find(|entry| entry.lua_path == path)
+    }
+
+    /// Return the first entry with the given plb path.
+    pub fn find_by_plb_path(&self, path: &str) -> Option<&ScriptEntry> {
+        self.entries.iter().find(|entry| entry.plb_path == path)
+    }
+
+    /// Return every entry whose lua path or plb path match the glob `pattern`. `*` match any sequence of
+    /// characters, including `/`, and `?` any single character.
+    pub fn find_by_path_glob(&self, pattern: &str) -> Vec<&ScriptEntry> {
+        self.entries.iter()
+            .filter(|entry| glob_match(pattern, &entry.lua_path) || glob_match(pattern, &entry.plb_path))
+            .collect()
+    }
+
+    /// Return the index of the first entry matching the predicate.
+    pub fn position<P: FnMut(&ScriptEntry) -> bool>(&self, pred: P) -> Option<usize> {
+        self.entries.iter().position(pred)
+    }
+
+    /// Return the index of the first entry with the given entity name.
+    pub fn position_by_entity_name(&self, name: &str) -> Option<usize> {
+        self.position(|entry| entry.entity_name == name)
+    }
+
+    /// Return the flags of the first entry with the given entity name.
+    pub fn flags_of(&self, entity_name: &str) -> Option<[u32; 4]> {
+        self.entries.iter().find(|entry| entry.entity_name == entity_name).map(|entry| entry.flags)
+    }
+
+    /// Set the flags of the first entry with the given entity name. Other entries with the same name are left
+    /// unchanged.
+    ///
+    /// Return false if no entry has this name.
+    pub fn set_flags_of(&mut self, entity_name: &str, flags: [u32; 4]) -> bool {
+        match self.entries.iter_mut().find(|entry| entry.entity_name == entity_name) {
+            Some(entry) => {
+                entry.flags = flags;
+                true
+            },
+            None => false,
+        }
+    }
+
+    /// Return the utf-8 strings (entity and map names) referenced more than once, with their number of references,
+    /// sorted by string. Each of them would be written only once.
+    pub fn shared_utf8_strings(&self) -> Vec<(&str, usize)> {
+        shared_strings(self.entries.iter().flat_map(|entry| [entry.entity_name.as_str(), entry.map_name.as_str()]))
+    }
+
+    /// Same as [`ScriptEntryList::shared_utf8_strings`], for the utf-16 strings (lua and plb paths).
+    pub fn shared_utf16_strings(&self) -> Vec<(&str, usize)> {
+        shared_strings(self.entries.iter().flat_map(|entry| [entry.lua_path.as_str(), entry.plb_path.as_str()]))
+    }
+
+    pub fn dedup_savings(&self) -> DedupSavings {
+        fn utf8_size(string: &str) -> usize {
+            string.len() + 1
+        }
+        fn utf16_size(string: &str) -> usize {
+            (string.encode_utf16().count() + 1) * 2
+        }
+
+        let mut savings = DedupSavings::default();
+        let mut utf8_seen = HashSet::new();
+        let mut utf16_seen = HashSet::new();
+        for entry in &self.entries {
+            for string in &[&entry.entity_name, &entry.map_name] {
+                if !utf8_seen.insert(string.as_str()) {
+                    savings.utf8_bytes += utf8_size(string);
+                }
+            }
+            for string in &[&entry.lua_path, &entry.plb_path] {
+                if !utf16_seen.insert(string.as_str()) {
+                    savings.utf16_bytes += utf16_size(string);
+                }
+            }
+        }
+        savings
+    }
+
+    /// The number of entries of this in-memory list. See [`ScriptEntryList::read_entry_count`] to read it from a file.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn first(&self) -> Option<&ScriptEntry> {
+        self.entries.first()
+    }
+
+    pub fn first_mut(&mut self) -> Option<&mut ScriptEntry> {
+        self.entries.first_mut()
+    }
+
+    pub fn last(&self) -> Option<&ScriptEntry> {
+        self.entries.last()
+    }
+
+    pub fn last_mut(&mut self) -> Option<&mut ScriptEntry> {
+        self.entries.last_mut()
+    }
+
+    /// Swap two entries. Panic if either index is out of bounds.
+    pub fn swap(&mut self, a: usize, b: usize) {
+        self.entries.swap(a, b)
+    }
+
+    /// Remove an entry, replacing it with the last one. Panic if `index` is out of bounds.
+    pub fn swap_remove(&mut self, index: usize) -> ScriptEntry {
+        self.entries.swap_remove(index)
+    }
+
+    /// Reverse the order of the entries, and so the order they are written in.
+    pub fn reverse(&mut self) {
+        self.entries.reverse();
+    }
+
+    /// Reorder the entries so they appear in the same order as the entries with the same entity name in `reference`.
+    ///
+    /// Entries whose entity name isn't in `reference` are kept at the end, in their current order. If an entity
+    /// name appears multiple times, the n-th occurrence in this list is matched with the n-th one in `reference`.
+    pub fn reorder_like(&mut self, reference: &ScriptEntryList) {
+        let mut remaining: Vec<Option<ScriptEntry>> = self.entries.drain(..).map(Some).collect();
+        for reference_entry in &reference.entries {
+            let matching = remaining.iter_mut().find(|entry| match entry {
+                Some(entry) => entry.entity_name == reference_entry.entity_name,
+                None => false,
             });
+            if let Some(entry) = matching.and_then(Option::take) {
+                self.entries.push(entry);
+            };
         };
+        self.entries.extend(remaining.into_iter().flatten());
+    }
 
-        Ok(ScriptEntryList {
-            entries
+    /// Index the entries by entity name. If multiple entries share a name, the last one wins.
+    pub fn into_btree_by_entity(self) -> BTreeMap<String, ScriptEntry> {
+        self.entries.into_iter().map(|entry| (entry.entity_name.clone(), entry)).collect()
+    }
+
+    /// Index the entries by entity name. If multiple entries share a name, the last one wins.
+    pub fn btree_by_entity(&self) -> BTreeMap<&str, &ScriptEntry> {
+        self.entries.iter().map(|entry| (entry.entity_name.as_str(), entry)).collect()
+    }
+
+    /// Return, for each map, the entity names used by more than one entry of that map, in order of first
+    /// appearance. Maps without such duplicate aren't included.
+    pub fn duplicate_entities_per_map(&self) -> HashMap<String, Vec<String>> {
+        let mut count: HashMap<(&str, &str), usize> = HashMap::new();
+        for entry in &self.entries {
+            *count.entry((&entry.map_name, &entry.entity_name)).or_insert(0) += 1;
+        };
+        let mut result: HashMap<String, Vec<String>> = HashMap::new();
+        for entry in &self.entries {
+            if let Some(entry_count) = count.get_mut(&(entry.map_name.as_str(), entry.entity_name.as_str())) {
+                if *entry_count > 1 {
+                    result.entry(entry.map_name.clone()).or_default().push(entry.entity_name.clone());
+                    // only list it once
+                    *entry_count = 0;
+                }
+            }
+        };
+        result
+    }
+
+    /// Iterate over the runs of consecutive entries sharing the same map name, in order.
+    ///
+    /// Only consecutive entries are grouped: a map can appear multiple times if the list isn't sorted by map first.
+    pub fn iter_grouped_by_map(&self) -> impl Iterator<Item = (&str, &[ScriptEntry])> + '_ {
+        let mut rest = &self.entries[..];
+        std::iter::from_fn(move || {
+            let first = rest.first()?;
+            let len = rest.iter().position(|entry| entry.map_name != first.map_name).unwrap_or(rest.len());
+            let (group, remaining) = rest.split_at(len);
+            rest = remaining;
+            Some((first.map_name.as_str(), group))
         })
     }
 
-    pub fn write_to_file<F: Write + Seek>(&self, file: &mut F) -> Result<(), ScriptEntryListError> {
-        let mut sir0_pointers = Vec::new();
-        file.write_all(b"SIR0")?;
+    /// Count how many entries use each lua or plb path. An entry using the same path for both is counted once.
+    pub fn path_usage(&self) -> HashMap<&str, usize> {
+        let mut usage = HashMap::new();
+        for entry in &self.entries {
+            *usage.entry(entry.lua_path.as_str()).or_insert(0) += 1;
+            if entry.plb_path != entry.lua_path {
+                *usage.entry(entry.plb_path.as_str()).or_insert(0) += 1;
+            };
+        };
+        usage
+    }
 
-        // pointer content data
-        sir0_pointers.push(file.seek(SeekFrom::Current(0))? as u32);
-        file.write_all(&u32::to_le_bytes(16))?;
+    /// Count how many entries use each entity name.
+    pub fn entity_usage(&self) -> HashMap<&str, usize> {
+        let mut usage = HashMap::new();
+        for entry in &self.entries {
+            *usage.entry(entry.entity_name.as_str()).or_insert(0) += 1;
+        };
+        usage
+    }
 
-        // pointer specific to sir0
-        sir0_pointers.push(file.seek(SeekFrom::Current(0))? as u32);
-        file.write_all(&[0; 4])?; //TODO:
+    /// Remove `prefix` from the start of every lua and plb path starting with it. Return the number of paths changed.
+    pub fn strip_path_prefix(&mut self, prefix: &str) -> usize {
+        if prefix.is_empty() {
+            return 0;
+        };
+        let mut changed = 0;
+        for entry in &mut self.entries {
+            for path in [&mut entry.lua_path, &mut entry.plb_path] {
+                if path.starts_with(prefix) {
+                    path.drain(..prefix.len());
+                    changed += 1;
+                }
+            }
+        };
+        changed
+    }
 
-        // magic
-        file.write_all(&[0; 4])?;
+    /// Add `prefix` at the start of every lua and plb path. Return the number of paths changed.
+    pub fn prepend_path_prefix(&mut self, prefix: &str) -> usize {
+        if prefix.is_empty() {
+            return 0;
+        };
+        for entry in &mut self.entries {
+            for path in [&mut entry.lua_path, &mut entry.plb_path] {
+                path.insert_str(0, prefix);
+            }
+        };
+        self.entries.len() * 2
+    }
 
-        // content data header
-        // entry_count
-        file.write_all(&u32::to_le_bytes(self.entries.len() as u32))?;
+    /// Build a new list by applying `f` to every entry.
+    pub fn map_entries<F: FnMut(ScriptEntry) -> ScriptEntry>(self, f: F) -> ScriptEntryList {
+        ScriptEntryList::from_entries(self.entries.into_iter().map(f).collect())
+    }
 
-        // pointer to list of pointer to entry
-        sir0_pointers.push(file.seek(SeekFrom::Current(0))? as u32);
-        file.write_all(&u32::to_le_bytes(24))?;
+    /// Build a new list by applying `f` to every entry, dropping those it map to `None`.
+    pub fn filter_map_entries<F: FnMut(ScriptEntry) -> Option<ScriptEntry>>(self, f: F) -> ScriptEntryList {
+        ScriptEntryList::from_entries(self.entries.into_iter().filter_map(f).collect())
+    }
 
+    /// Append a copy of the first entry named `source_name`, renamed to `new_name`.
+    pub fn duplicate_entry(&mut self, source_name: &str, new_name: &str) -> Result<(), ScriptEntryListError> {
+        if self.position_by_entity_name(new_name).is_some() {
+            return Err(ScriptEntryListError::EntityNameTaken(new_name.to_string()));
+        };
+        let source = self.position_by_entity_name(source_name)
+            .ok_or_else(|| ScriptEntryListError::EntityNotFound(source_name.to_string()))?;
+        let mut entry = self.entries[source].clone();
+        entry.entity_name = new_name.to_string();
+        self.entries.push(entry);
+        Ok(())
+    }
 
-        // list of pointer to entry -- will be overwritten
-        //TODO:
-        //let mut list_pointer_to_entry = Vec::new();
-        for _ in 0..self.entries.len() {
-            sir0_pointers.push(file.seek(SeekFrom::Current(0))? as u32);
-            file.write_all(&[0; 4])?;
+    /// Set the map name of every entry of the map `old` to `new`. Return the number of entries changed.
+    pub fn rename_map(&mut self, old: &str, new: &str) -> usize {
+        let mut changed = 0;
+        for entry in &mut self.entries {
+            if entry.map_name == old {
+                entry.map_name = new.to_string();
+                changed += 1;
+            }
         };
+        changed
+    }
 
-        // list of entries -- will be overwritten
-        let list_of_entries_pointer = file.seek(SeekFrom::Current(0))?;
-        for _ in 0..self.entries.len() {
-            sir0_pointers.push(file.seek(SeekFrom::Current(0))? as u32);
-            file.write_all(&[0; 4])?;
-            sir0_pointers.push(file.seek(SeekFrom::Current(0))? as u32);
-            file.write_all(&[0; 4])?;
-            sir0_pointers.push(file.seek(SeekFrom::Current(0))? as u32);
-            file.write_all(&[0; 4])?;
-            sir0_pointers.push(file.seek(SeekFrom::Current(0))? as u32);
-            file.write_all(&[0; 4])?;
-            sir0_pointers.push(file.seek(SeekFrom::Current(0))? as u32);
-            file.write_all(&[0; 4])?;
+    /// Compare the entries of the two lists, ignoring their order.
+    pub fn content_equals(&self, other: &ScriptEntryList) -> bool {
+        fn sorted(list: &ScriptEntryList) -> Vec<&ScriptEntry> {
+            let mut entries: Vec<&ScriptEntry> = list.entries.iter().collect();
+            // sorted by entity name first, the other fields only break ties
+            entries.sort_by_key(|entry| (&entry.entity_name, &entry.map_name, &entry.lua_path, &entry.plb_path, entry.flags));
+            entries
         }
+        self.entries.len() == other.entries.len() && sorted(self) == sorted(other)
+    }
 
-        // list of flags
-        // the original compiler doesn't seem to try to elimate double entry
-        let mut flags_pointer = Vec::new();
+    /// Pair the entries of this list with the ones of `other` with the same strings (see
+    /// [`ScriptEntry::eq_ignoring_flags`]), and return the pairs whose flags differ, in the order of this list.
+    ///
+    /// If identical entries appear multiple times, the n-th occurrence in this list is paired with the n-th one in
+    /// `other`.
+    pub fn find_flag_only_differences<'a>(&'a self, other: &'a ScriptEntryList) -> Vec<(&'a ScriptEntry, &'a ScriptEntry)> {
+        let mut paired = vec![false; other.entries.len()];
+        let mut differences = Vec::new();
+        for entry in &self.entries {
+            let matching = other.entries.iter()
+                .enumerate()
+                .find(|(index, other_entry)| !paired[*index] && entry.eq_ignoring_flags(other_entry));
+            if let Some((index, other_entry)) = matching {
+                paired[index] = true;
+                if entry.flags != other_entry.flags {
+                    differences.push((entry, other_entry));
+                };
+            };
+        };
+        differences
+    }
+
+    /// Return the flags of every entry, in order.
+    pub fn flag_table(&self) -> Vec<[u32; 4]> {
+        self.entries.iter().map(|entry| entry.flags).collect()
+    }
+
+    /// Replace the flags of every entry by the ones at the same index of `flags`, as returned by
+    /// [`ScriptEntryList::flag_table`]. Fail with [`ScriptEntryListError::ColumnLengthMismatch`] and leave the list
+    /// unchanged if there isn't one per entry.
+    pub fn set_flag_table(&mut self, flags: &[[u32; 4]]) -> Result<(), ScriptEntryListError> {
+        if flags.len() != self.entries.len() {
+            return Err(ScriptEntryListError::ColumnLengthMismatch { expected: self.entries.len(), found: flags.len() });
+        };
+        for (entry, flags) in self.entries.iter_mut().zip(flags) {
+            entry.flags = *flags;
+        };
+        Ok(())
+    }
+
+    /// For each entity present in both lists whose flags differ, return its name and the changed bits, as given
+    /// by [`flag_diff`], in the order of this list. Only the first entry with a given name in each list is compared.
+    pub fn flag_changes(&self, other: &ScriptEntryList) -> Vec<(String, Vec<(usize, u8)>)> {
+        let mut seen = HashSet::new();
+        let mut changes = Vec::new();
+        for entry in &self.entries {
+            if !seen.insert(entry.entity_name.as_str()) {
+                continue;
+            };
+            if let Some(other_entry) = other.entries.iter().find(|other_entry| other_entry.entity_name == entry.entity_name) {
+                let diff = flag_diff(entry, other_entry);
+                if !diff.is_empty() {
+                    changes.push((entry.entity_name.clone(), diff));
+                };
+            };
+        };
+        changes
+    }
+
+    /// For each of the 4 flag words and each of their 32 bits, count how many entries have that bit set.
+    pub fn flag_bit_histogram(&self) -> [[usize; 32]; 4] {
+        let mut histogram = [[0; 32]; 4];
         for entry in &self.entries {
-            flags_pointer.push(file.seek(SeekFrom::Current(0))?);
-            for flag_id in 0..4 {
-                file.write_all(&u32::to_le_bytes(entry.flags[flag_id]))?;
+            for (word_histogram, word) in histogram.iter_mut().zip(entry.flags.iter()) {
+                for (bit, count) in word_histogram.iter_mut().enumerate() {
+                    if word & (1 << bit) != 0 {
+                        *count += 1;
+                    }
+                }
             }
         }
+        histogram
+    }
 
-        // strings
-        let mut utf16_string_to_write_set = HashSet::new();
-        let mut utf8_string_to_write_set = HashSet::new();
-        for entry in &self.entries {
-            utf16_string_to_write_set.insert(entry.lua_path.clone());
-            utf16_string_to_write_set.insert(entry.plb_path.clone());
-            utf8_string_to_write_set.insert(entry.entity_name.clone());
-            utf8_string_to_write_set.insert(entry.map_name.clone());
+    /// Replace the flags of every entry whose flags are a key of `table` by the associated value.
+    ///
+    /// Return the number of entries whose flags changed.
+    pub fn remap_flags(&mut self, table: &HashMap<[u32; 4], [u32; 4]>) -> usize {
+        let mut modified = 0;
+        for entry in &mut self.entries {
+            if let Some(new_flags) = table.get(&entry.flags) {
+                if *new_flags != entry.flags {
+                    entry.flags = *new_flags;
+                    modified += 1;
+                }
+            }
         }
+        modified
+    }
+
+    pub fn write_to_file<F: Write + Seek>(&self, file: &mut F) -> Result<(), ScriptEntryListError> {
+        self.write_to_file_with_options(file, &WriteOptions::default())
+    }
 
-        let mut utf16_string_map = HashMap::new();
-        for string in utf16_string_to_write_set {
-            let string_start_offset = file.seek(SeekFrom::Current(0))?;
-            file.write_all(&string_to_utf16(&string))?;
-            file.write_all(&[0; 2])?;
-            utf16_string_map.insert(string, string_start_offset);
+    /// Same as [`ScriptEntryList::write_to_file`], but parse the written file back before writing it to `file`,
+    /// checking every entry matches this list. Nothing is written to `file` if that check fails.
+    pub fn write_to_file_verified<F: Write + Seek>(&self, file: &mut F) -> Result<(), ScriptEntryListError> {
+        let mut buffer = Cursor::new(Vec::new());
+        self.write_to_file(&mut buffer)?;
+        if &ScriptEntryList::new_from_file(&mut buffer)? != self {
+            return Err(ScriptEntryListError::VerificationFailed);
         };
+        file.seek(SeekFrom::Start(0))?;
+        file.write_all(&buffer.into_inner())?;
+        Ok(())
+    }
 
+    pub fn write_to_file_with_options<F: Write + Seek>(&self, file: &mut F, options: &WriteOptions) -> Result<(), ScriptEntryListError> {
+        self.write_block(file, 0, options)
+    }
 
-        let mut utf8_string_map = HashMap::new();
-        for string in utf8_string_to_write_set {
-            let string_start_offset = file.seek(SeekFrom::Current(0))?;
-            file.write_all(string.as_bytes())?;
-            file.write_all(&[0])?;
-            utf8_string_map.insert(string, string_start_offset);
+    /// Same as [`ScriptEntryList::write_to_file`], for output that can't seek (like the standard output). The
+    /// file is built in memory, then copied to `writer`.
+    pub fn to_writer<W: Write>(&self, writer: &mut W) -> Result<(), ScriptEntryListError> {
+        let mut buffer = Cursor::new(Vec::new());
+        self.write_to_file(&mut buffer)?;
+        writer.write_all(&buffer.into_inner())?;
+        Ok(())
+    }
+
+    /// Write every list as its own SIR0 block, back to back from the start of `file`, so they can be read back with
+    /// [`ScriptEntryList::read_all`]. Each block is padded to a multiple of 16 bytes.
+    pub fn write_all<F: Write + Seek>(lists: &[ScriptEntryList], file: &mut F) -> Result<(), ScriptEntryListError> {
+        ScriptEntryList::write_all_with_options(lists, file, &WriteOptions {
+            final_alignment: 16,
+            ..WriteOptions::default()
+        })
+    }
+
+    /// Same as [`ScriptEntryList::write_all`], writing every block with the given options.
+    pub fn write_all_with_options<F: Write + Seek>(lists: &[ScriptEntryList], file: &mut F, options: &WriteOptions) -> Result<(), ScriptEntryListError> {
+        let mut base = 0;
+        for list in lists {
+            list.write_block(file, base, options)?;
+            base = file.stream_position()?;
         };
+        Ok(())
+    }
 
-        let sir0_list_pointer = file.seek(SeekFrom::Current(0))?;
+    /// Write the SIR0 block starting at offset `base` of `file`, rather than at the start of the file.
+    ///
+    /// All pointers (including the SIR0 footer) are relative to the start of the block, as the game expect, so
+    /// the block can be extracted as is. Once done, the cursor is placed at the end of the block.
+    pub fn write_to_file_at<F: Write + Seek>(&self, file: &mut F, base: u64) -> Result<(), ScriptEntryListError> {
+        self.write_block(file, base, &WriteOptions::default())
+    }
 
-        // write list of entries
-        file.seek(SeekFrom::Start(list_of_entries_pointer))?;
-        let mut entries_pointer = Vec::new();
-        for (entryid, entry) in self.entries.iter().enumerate() {
-            entries_pointer.push(file.seek(SeekFrom::Current(0))?);
-            file.write_all(&u32::to_le_bytes(utf8_string_map[&entry.entity_name] as u32))?;
-            file.write_all(&u32::to_le_bytes(utf8_string_map[&entry.map_name] as u32))?;
-            file.write_all(&u32::to_le_bytes(utf16_string_map[&entry.lua_path] as u32))?;
-            file.write_all(&u32::to_le_bytes(utf16_string_map[&entry.plb_path] as u32))?;
-            file.write_all(&u32::to_le_bytes(flags_pointer[entryid] as u32))?;
-        }
+    fn write_block<F: Write + Seek>(&self, file: &mut F, base: u64, options: &WriteOptions) -> Result<(), ScriptEntryListError> {
+        if options.append_crc32 {
+            // the output can't be read back, so build it in memory first
+            let mut buffer = Cursor::new(Vec::new());
+            self.write_block(&mut buffer, 0, &WriteOptions {
+                append_crc32: false,
+                ..options.clone()
+            })?;
+            let mut data = buffer.into_inner();
+            let crc = crc32(&data);
+            data.extend_from_slice(&crc.to_le_bytes());
+            file.seek(SeekFrom::Start(base))?;
+            file.write_all(&data)?;
+            return Ok(());
+        };
+        let plan = self.plan_layout_with_options(options)?;
+
+        file.seek(SeekFrom::Start(base))?;
+        file.write_all(&options.magic)?;
+        file.write_all(&u32::to_le_bytes(plan.content_data as u32))?;
+        file.write_all(&u32::to_le_bytes(plan.footer as u32))?;
+        // magic
+        file.write_all(&[0; 4])?;
+
+        // content data header
+        file.write_all(&u32::to_le_bytes(self.entries.len() as u32))?;
+        file.write_all(&u32::to_le_bytes(plan.entry_pointer_list as u32))?;
+        file.write_all(&vec![0; options.content_header_len - 8])?;
 
-        // write list of pointer to entries
-        file.seek(SeekFrom::Start(24))?;
-        for pointer in entries_pointer {
-            file.write_all(&u32::to_le_bytes(pointer as u32))?;
+        // list of pointer to entry
+        // it is written sequentially, so make sure it does end up where the header point to
+        debug_assert_eq!(relative_position(file, base)?, plan.entry_pointer_list);
+        for entry_layout in &plan.entries {
+            file.write_all(&u32::to_le_bytes(entry_layout.record as u32))?;
+        };
+        if options.pointer_list_sentinel {
+            file.write_all(&[0; 4])?;
         };
 
+        // list of entries
+        if let Some(first) = plan.entries.first() {
+            debug_assert_eq!(relative_position(file, base)?, first.record);
+        };
+        for entry_layout in &plan.entries {
+            file.write_all(&u32::to_le_bytes(entry_layout.entity_name as u32))?;
+            file.write_all(&u32::to_le_bytes(entry_layout.map_name as u32))?;
+            file.write_all(&u32::to_le_bytes(entry_layout.lua_path as u32))?;
+            file.write_all(&u32::to_le_bytes(entry_layout.plb_path as u32))?;
+            file.write_all(&u32::to_le_bytes(entry_layout.flags as u32))?;
+        };
 
+        // list of flags
+        for flags in &plan.flag_blocks {
+            for flag in flags {
+                file.write_all(&u32::to_le_bytes(*flag))?;
+            }
+        };
 
-        // write sir0 end
-        file.seek(SeekFrom::Start(sir0_list_pointer))?;
+        // strings
+        for string in &plan.strings {
+            file.write_all(&string.encoded)?;
+        };
 
         // write a padding
-        while file.seek(SeekFrom::Current(0))?%4 != 0 {
+        while relative_position(file, base)? < plan.footer {
             file.write_all(&[0])?;
         };
 
-        let sir0_list_padded = file.seek(SeekFrom::Current(0))?;
-
         // write the sir0 pointer list
-        write_sir0_footer(file, sir0_pointers)?;
-
+        file.write_all(&plan.footer_data)?;
 
-        file.seek(SeekFrom::Start(8))?;
-        file.write_all(&u32::to_le_bytes(sir0_list_padded as u32))?;
+        // pad the whole file
+        while relative_position(file, base)? < plan.total_len {
+            file.write_all(&[0])?;
+        };
         Ok(())
     }
 }
@@ -0,0 +1,123 @@
+use crate::ScriptEntryListError;
+use std::fmt;
+use std::io::{Read, Seek, SeekFrom};
+use std::sync::Arc;
+
+/// An encoding used to store the NUL-terminated strings of an entry list file.
+pub trait StringCodec: fmt::Debug + Send + Sync {
+    /// The size in bytes of a code unit. The NUL terminator is a zeroed code unit, and strings are aligned on it.
+    fn unit_size(&self) -> usize;
+
+    /// Encode the string, without NUL terminator. Characters that can't be encoded may be replaced, as the
+    /// writer check the result decode back to the same string.
+    fn encode(&self, string: &str) -> Vec<u8>;
+
+    /// Decode the bytes of a string, without NUL terminator.
+    fn decode(&self, bytes: &[u8]) -> Result<String, ScriptEntryListError>;
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Utf8Codec;
+
+impl StringCodec for Utf8Codec {
+    fn unit_size(&self) -> usize {
+        1
+    }
+
+    fn encode(&self, string: &str) -> Vec<u8> {
+        string.as_bytes().to_vec()
+    }
+
+    fn decode(&self, bytes: &[u8]) -> Result<String, ScriptEntryListError> {
+        Ok(String::from_utf8(bytes.to_vec())?)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Utf16LeCodec;
+
+impl StringCodec for Utf16LeCodec {
+    fn unit_size(&self) -> usize {
+        2
+    }
+
+    fn encode(&self, string: &str) -> Vec<u8> {
+        let mut result = Vec::new();
+        for chara in string.encode_utf16() {
+            result.extend_from_slice(&u16::to_le_bytes(chara))
+        };
+        result
+    }
+
+    fn decode(&self, bytes: &[u8]) -> Result<String, ScriptEntryListError> {
+        if bytes.len() % 2 != 0 {
+            return Err(ScriptEntryListError::OddUtf16Length(bytes.len()));
+        };
+        let units: Vec<u16> = bytes.chunks(2).map(|unit| u16::from_le_bytes([unit[0], unit[1]])).collect();
+        // decode the whole string at once, so surrogate pairs are handled
+        Ok(String::from_utf16(&units)?)
+    }
+}
+
+/// The encoding of each string field of an entry.
+#[derive(Debug, Clone)]
+pub struct FieldCodecs {
+    pub entity_name: Arc<dyn StringCodec>,
+    pub map_name: Arc<dyn StringCodec>,
+    pub lua_path: Arc<dyn StringCodec>,
+    pub plb_path: Arc<dyn StringCodec>,
+}
+
+/// utf-8 names and utf-16 paths, as used by Super Mystery Dungeon. This is the only game whose files are known:
+/// files of other games may need other encodings.
+impl Default for FieldCodecs {
+    fn default() -> FieldCodecs {
+        FieldCodecs {
+            entity_name: Arc::new(Utf8Codec),
+            map_name: Arc::new(Utf8Codec),
+            lua_path: Arc::new(Utf16LeCodec),
+            plb_path: Arc::new(Utf16LeCodec),
+        }
+    }
+}
+
+/// Encode the string with its NUL terminator, checking it will be read back identically.
+pub(crate) fn encode_field(codec: &dyn StringCodec, string: &str) -> Result<Vec<u8>, ScriptEntryListError> {
+    let unit_size = codec.unit_size();
+    let mut encoded = codec.encode(string);
+    let has_nul_unit = encoded.chunks(unit_size).any(|unit| unit.iter().all(|byte| *byte == 0));
+    if encoded.len() % unit_size != 0 || has_nul_unit || codec.decode(&encoded)? != string {
+        return Err(ScriptEntryListError::UnencodableString(string.to_string()));
+    };
+    encoded.extend(std::iter::repeat(0).take(unit_size));
+    Ok(encoded)
+}
+
+/// Read a NUL-terminated string at `reference`.
+///
+/// A null reference is read as an empty string, as offset 0 is the SIR0 header. Fail with
+/// [`ScriptEntryListError::UnterminatedString`] if `max_len` is set and no NUL terminator is found in the first
+/// `max_len` bytes.
+pub fn read_referenced_string<F: Read + Seek>(file: &mut F, reference: u64, codec: &dyn StringCodec, max_len: Option<usize>) -> Result<String, ScriptEntryListError> {
+    codec.decode(&read_referenced_bytes(file, reference, codec.unit_size(), max_len)?)
+}
+
+/// Same as [`read_referenced_string`], but return the encoded string, without its terminator.
+pub(crate) fn read_referenced_bytes<F: Read + Seek>(file: &mut F, reference: u64, unit_size: usize, max_len: Option<usize>) -> Result<Vec<u8>, ScriptEntryListError> {
+    if reference == 0 {
+        return Ok(Vec::new());
+    };
+    file.seek(SeekFrom::Start(reference))?;
+    let mut result = Vec::new();
+    let mut buffer = vec![0; unit_size];
+    loop {
+        file.read_exact(&mut buffer)?;
+        if buffer.iter().all(|byte| *byte == 0) {
+            return Ok(result);
+        };
+        if max_len.is_some_and(|max_len| result.len() >= max_len) {
+            return Err(ScriptEntryListError::UnterminatedString { offset: reference });
+        };
+        result.extend_from_slice(&buffer);
+    }
+}
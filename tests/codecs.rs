@@ -0,0 +1,87 @@
+//! Plugging in string encodings other than the default ones.
+
+use pmd_script_entry_list::{FieldCodecs, ParseOptions, ScriptEntry, ScriptEntryList, ScriptEntryListError, StringCodec, Utf16LeCodec, WriteOptions};
+use std::io::Cursor;
+use std::sync::Arc;
+
+mod common;
+use common::write_with;
+
+/// utf-32, to have a code unit larger than any of the built-in codecs
+#[derive(Debug)]
+struct Utf32LeCodec;
+
+impl StringCodec for Utf32LeCodec {
+    fn unit_size(&self) -> usize {
+        4
+    }
+
+    fn encode(&self, string: &str) -> Vec<u8> {
+        string.chars().flat_map(|chara| (chara as u32).to_le_bytes()).collect()
+    }
+
+    fn decode(&self, bytes: &[u8]) -> Result<String, ScriptEntryListError> {
+        Ok(bytes.chunks(4)
+            .map(|unit| std::char::from_u32(u32::from_le_bytes([unit[0], unit[1], unit[2], unit[3]])).unwrap_or('?'))
+            .collect())
+    }
+}
+
+fn codecs() -> FieldCodecs {
+    FieldCodecs {
+        entity_name: Arc::new(Utf32LeCodec),
+        map_name: Arc::new(Utf16LeCodec),
+        ..FieldCodecs::default()
+    }
+}
+
+#[test]
+fn custom_codecs_round_trip() {
+    // the same strings in fields with different encodings, and odd lengths to require padding
+    let list = ScriptEntryList::from_entries(
+        ["a", "shared", "odd"].iter()
+            .map(|name| ScriptEntry {
+                entity_name: name.to_string(),
+                map_name: "shared".to_string(),
+                lua_path: name.to_string(),
+                plb_path: "shared".to_string(),
+                flags: [0; 4],
+            })
+            .collect(),
+    );
+    let write_options = WriteOptions {
+        codecs: codecs(),
+        ..WriteOptions::default()
+    };
+    let buffer = write_with(&list, &write_options);
+    let parse_options = ParseOptions {
+        codecs: codecs(),
+        ..ParseOptions::default()
+    };
+    let (parsed, warnings) = ScriptEntryList::new_from_file_with_options(&mut Cursor::new(&buffer), &parse_options).unwrap();
+    assert_eq!(parsed, list);
+    assert!(warnings.is_empty());
+
+    let plan = list.plan_layout_with_options(&write_options).unwrap();
+    for (entry, layout) in list.entries.iter().zip(&plan.entries) {
+        assert_eq!(layout.entity_name % 4, 0);
+        assert_eq!(layout.map_name % 2, 0);
+        assert_eq!(layout.lua_path % 2, 0);
+        // strings are only shared between fields with the same encoding
+        assert_ne!(layout.entity_name, layout.lua_path);
+        assert_eq!(layout.map_name, layout.plb_path);
+        if entry.entity_name == "shared" {
+            assert_eq!(layout.lua_path, layout.map_name);
+        };
+    }
+
+    // the default codecs read the utf-32 names differently
+    let default_parse = ScriptEntryList::new_from_file(&mut Cursor::new(&buffer));
+    assert!(default_parse.map_or(true, |default_parse| default_parse != list));
+}
+
+#[test]
+fn utf16_decode_of_an_odd_length() {
+    assert_eq!(Utf16LeCodec.decode(&[b'a', 0, b'b', 0]).unwrap(), "ab");
+    assert!(matches!(Utf16LeCodec.decode(&[b'a', 0, b'b']), Err(ScriptEntryListError::OddUtf16Length(3))));
+}
@@ -0,0 +1,61 @@
+//! Helpers shared by the integration tests.
+#![allow(dead_code)]
+
+use pmd_script_entry_list::{ScriptEntry, ScriptEntryList, WriteOptions};
+use std::io::Cursor;
+
+pub mod value;
+
+pub fn entry(name: &str, lua_path: &str, flags: [u32; 4]) -> ScriptEntry {
+    ScriptEntry {
+        entity_name: name.to_string(),
+        map_name: "map".to_string(),
+        lua_path: lua_path.to_string(),
+        plb_path: "script.plb".to_string(),
+        flags,
+    }
+}
+
+/// Three entries with distinct names, paths and flags, sharing their map name
+pub fn sample_list() -> ScriptEntryList {
+    ScriptEntryList::from_entries(
+        (0..3)
+            .map(|id| ScriptEntry {
+                entity_name: format!("NPC_{}", id),
+                map_name: "map".to_string(),
+                lua_path: format!("script/{}.lua", id),
+                plb_path: format!("script/{}.plb", id),
+                flags: [id, 0, 0, 0],
+            })
+            .collect(),
+    )
+}
+
+/// A list with a single entry
+pub fn single_entry_list() -> ScriptEntryList {
+    entry("NPC", "script.lua", [1, 2, 3, 4]).to_single_entry_list()
+}
+
+pub fn write(list: &ScriptEntryList) -> Vec<u8> {
+    write_with(list, &WriteOptions::default())
+}
+
+pub fn write_with(list: &ScriptEntryList, options: &WriteOptions) -> Vec<u8> {
+    let mut buffer = Cursor::new(Vec::new());
+    list.write_to_file_with_options(&mut buffer, options).unwrap();
+    buffer.into_inner()
+}
+
+pub fn read_u32(buffer: &[u8], offset: u64) -> u32 {
+    let offset = offset as usize;
+    u32::from_le_bytes([buffer[offset], buffer[offset + 1], buffer[offset + 2], buffer[offset + 3]])
+}
+
+pub fn patch_u32(buffer: &mut [u8], offset: u64, value: u32) {
+    let offset = offset as usize;
+    buffer[offset..offset + 4].copy_from_slice(&value.to_le_bytes());
+}
+
+pub fn contains(haystack: &[u8], needle: &[u8]) -> bool {
+    haystack.windows(needle.len()).any(|window| window == needle)
+}
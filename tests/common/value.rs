@@ -0,0 +1,237 @@
+//! A minimal in-memory serde data format, to test serialization without a serde format crate.
+//!
+//! Only what the types of this crate use is supported: unsigned integers, strings, sequences (including tuples and
+//! arrays) and structs.
+
+use serde::de::value::{Error, MapDeserializer, SeqDeserializer};
+use serde::de::{DeserializeOwned, IntoDeserializer, Visitor};
+use serde::ser::{Error as _, Impossible, SerializeSeq, SerializeStruct, SerializeTuple};
+use serde::{forward_to_deserialize_any, Deserializer, Serialize, Serializer};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Value {
+    U64(u64),
+    String(String),
+    Seq(Vec<Value>),
+    /// a struct, with its fields in order
+    Struct(Vec<(String, Value)>),
+}
+
+pub fn to_value<T: ?Sized + Serialize>(value: &T) -> Result<Value, Error> {
+    value.serialize(ValueSerializer)
+}
+
+pub fn from_value<T: DeserializeOwned>(value: Value) -> Result<T, Error> {
+    T::deserialize(value)
+}
+
+fn unsupported<T>(what: &str) -> Result<T, Error> {
+    Err(Error::custom(format!("{} isn't supported", what)))
+}
+
+struct ValueSerializer;
+
+impl Serializer for ValueSerializer {
+    type Ok = Value;
+    type Error = Error;
+    type SerializeSeq = SeqSerializer;
+    type SerializeTuple = SeqSerializer;
+    type SerializeTupleStruct = Impossible<Value, Error>;
+    type SerializeTupleVariant = Impossible<Value, Error>;
+    type SerializeMap = Impossible<Value, Error>;
+    type SerializeStruct = StructSerializer;
+    type SerializeStructVariant = Impossible<Value, Error>;
+
+    fn serialize_bool(self, _: bool) -> Result<Value, Error> {
+        unsupported("bool")
+    }
+
+    fn serialize_i8(self, _: i8) -> Result<Value, Error> {
+        unsupported("i8")
+    }
+
+    fn serialize_i16(self, _: i16) -> Result<Value, Error> {
+        unsupported("i16")
+    }
+
+    fn serialize_i32(self, _: i32) -> Result<Value, Error> {
+        unsupported("i32")
+    }
+
+    fn serialize_i64(self, _: i64) -> Result<Value, Error> {
+        unsupported("i64")
+    }
+
+    fn serialize_u8(self, value: u8) -> Result<Value, Error> {
+        Ok(Value::U64(value as u64))
+    }
+
+    fn serialize_u16(self, value: u16) -> Result<Value, Error> {
+        Ok(Value::U64(value as u64))
+    }
+
+    fn serialize_u32(self, value: u32) -> Result<Value, Error> {
+        Ok(Value::U64(value as u64))
+    }
+
+    fn serialize_u64(self, value: u64) -> Result<Value, Error> {
+        Ok(Value::U64(value))
+    }
+
+    fn serialize_f32(self, _: f32) -> Result<Value, Error> {
+        unsupported("f32")
+    }
+
+    fn serialize_f64(self, _: f64) -> Result<Value, Error> {
+        unsupported("f64")
+    }
+
+    fn serialize_char(self, value: char) -> Result<Value, Error> {
+        Ok(Value::String(value.to_string()))
+    }
+
+    fn serialize_str(self, value: &str) -> Result<Value, Error> {
+        Ok(Value::String(value.to_string()))
+    }
+
+    fn serialize_bytes(self, _: &[u8]) -> Result<Value, Error> {
+        unsupported("bytes")
+    }
+
+    fn serialize_none(self) -> Result<Value, Error> {
+        unsupported("option")
+    }
+
+    fn serialize_some<T: ?Sized + Serialize>(self, _: &T) -> Result<Value, Error> {
+        unsupported("option")
+    }
+
+    fn serialize_unit(self) -> Result<Value, Error> {
+        unsupported("unit")
+    }
+
+    fn serialize_unit_struct(self, _: &'static str) -> Result<Value, Error> {
+        unsupported("unit struct")
+    }
+
+    fn serialize_unit_variant(self, _: &'static str, _: u32, _: &'static str) -> Result<Value, Error> {
+        unsupported("enum")
+    }
+
+    fn serialize_newtype_struct<T: ?Sized + Serialize>(self, _: &'static str, value: &T) -> Result<Value, Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: ?Sized + Serialize>(self, _: &'static str, _: u32, _: &'static str, _: &T) -> Result<Value, Error> {
+        unsupported("enum")
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<SeqSerializer, Error> {
+        Ok(SeqSerializer(Vec::with_capacity(len.unwrap_or(0))))
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<SeqSerializer, Error> {
+        Ok(SeqSerializer(Vec::with_capacity(len)))
+    }
+
+    fn serialize_tuple_struct(self, _: &'static str, _: usize) -> Result<Self::SerializeTupleStruct, Error> {
+        unsupported("tuple struct")
+    }
+
+    fn serialize_tuple_variant(self, _: &'static str, _: u32, _: &'static str, _: usize) -> Result<Self::SerializeTupleVariant, Error> {
+        unsupported("enum")
+    }
+
+    fn serialize_map(self, _: Option<usize>) -> Result<Self::SerializeMap, Error> {
+        unsupported("map")
+    }
+
+    fn serialize_struct(self, _: &'static str, len: usize) -> Result<StructSerializer, Error> {
+        Ok(StructSerializer(Vec::with_capacity(len)))
+    }
+
+    fn serialize_struct_variant(self, _: &'static str, _: u32, _: &'static str, _: usize) -> Result<Self::SerializeStructVariant, Error> {
+        unsupported("enum")
+    }
+}
+
+struct SeqSerializer(Vec<Value>);
+
+impl SerializeSeq for SeqSerializer {
+    type Ok = Value;
+    type Error = Error;
+
+    fn serialize_element<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), Error> {
+        self.0.push(to_value(value)?);
+        Ok(())
+    }
+
+    fn end(self) -> Result<Value, Error> {
+        Ok(Value::Seq(self.0))
+    }
+}
+
+impl SerializeTuple for SeqSerializer {
+    type Ok = Value;
+    type Error = Error;
+
+    fn serialize_element<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), Error> {
+        SerializeSeq::serialize_element(self, value)
+    }
+
+    fn end(self) -> Result<Value, Error> {
+        SerializeSeq::end(self)
+    }
+}
+
+struct StructSerializer(Vec<(String, Value)>);
+
+impl SerializeStruct for StructSerializer {
+    type Ok = Value;
+    type Error = Error;
+
+    fn serialize_field<T: ?Sized + Serialize>(&mut self, key: &'static str, value: &T) -> Result<(), Error> {
+        self.0.push((key.to_string(), to_value(value)?));
+        Ok(())
+    }
+
+    fn end(self) -> Result<Value, Error> {
+        Ok(Value::Struct(self.0))
+    }
+}
+
+impl<'de> Deserializer<'de> for Value {
+    type Error = Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        match self {
+            Value::U64(value) => visitor.visit_u64(value),
+            Value::String(value) => visitor.visit_string(value),
+            Value::Seq(values) => {
+                let mut seq = SeqDeserializer::new(values.into_iter());
+                let result = visitor.visit_seq(&mut seq)?;
+                seq.end()?;
+                Ok(result)
+            },
+            Value::Struct(fields) => {
+                let mut map = MapDeserializer::new(fields.into_iter());
+                let result = visitor.visit_map(&mut map)?;
+                map.end()?;
+                Ok(result)
+            },
+        }
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string bytes byte_buf option unit unit_struct
+        newtype_struct seq tuple tuple_struct map struct enum identifier ignored_any
+    }
+}
+
+impl<'de> IntoDeserializer<'de, Error> for Value {
+    type Deserializer = Value;
+
+    fn into_deserializer(self) -> Value {
+        self
+    }
+}
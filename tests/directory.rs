@@ -0,0 +1,72 @@
+//! Building a list from a directory of scripts.
+
+use pmd_script_entry_list::ScriptEntryList;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// A directory in the system temporary directory, removed once dropped
+struct TempDir(PathBuf);
+
+impl TempDir {
+    fn new(name: &str) -> TempDir {
+        let path = std::env::temp_dir().join(format!("pmd_script_entry_list_{}_{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&path);
+        fs::create_dir_all(&path).unwrap();
+        TempDir(path)
+    }
+
+    fn add_file(&self, path: &str) {
+        let path = self.0.join(path);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, b"").unwrap();
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.0);
+    }
+}
+
+#[test]
+fn scripts_with_a_plb_are_listed() {
+    let dir = TempDir::new("scripts");
+    for path in ["town/shop.lua", "town/shop.plb", "a.lua", "a.plb", "orphan.lua", "town/deep/well.lua", "town/deep/well.plb", "notes.txt"] {
+        dir.add_file(path);
+    }
+
+    let naming = |path: &Path| (
+        path.file_stem().unwrap().to_string_lossy().to_uppercase(),
+        path.parent().map(|parent| parent.to_string_lossy().to_string()).unwrap_or_default(),
+    );
+    let list = ScriptEntryList::from_script_directory(&dir.0, &naming).unwrap();
+
+    let paths: Vec<(&str, &str)> = list.entries.iter().map(|entry| (entry.lua_path.as_str(), entry.plb_path.as_str())).collect();
+    assert_eq!(paths, vec![
+        ("a.lua", "a.plb"),
+        ("town/deep/well.lua", "town/deep/well.plb"),
+        ("town/shop.lua", "town/shop.plb"),
+    ]);
+    let names: Vec<&str> = list.entries.iter().map(|entry| entry.entity_name.as_str()).collect();
+    assert_eq!(names, vec!["A", "WELL", "SHOP"]);
+    assert_eq!(list.entries[0].map_name, "");
+    assert!(list.entries.iter().all(|entry| entry.flags == [0; 4]));
+}
+
+#[cfg(unix)]
+#[test]
+fn symlinks_are_skipped() {
+    use std::os::unix::fs::symlink;
+
+    let dir = TempDir::new("symlinks");
+    for path in ["town/shop.lua", "town/shop.plb", "linked.lua"] {
+        dir.add_file(path);
+    }
+    // a loop back to the root, and a link making "linked.lua" look like it has a plb
+    symlink(&dir.0, dir.0.join("town/loop")).unwrap();
+    symlink(dir.0.join("town/shop.plb"), dir.0.join("linked.plb")).unwrap();
+
+    let list = ScriptEntryList::from_script_directory(&dir.0, &|_| (String::new(), String::new())).unwrap();
+    let paths: Vec<&str> = list.entries.iter().map(|entry| entry.lua_path.as_str()).collect();
+    assert_eq!(paths, vec!["town/shop.lua"]);
+}
@@ -0,0 +1,83 @@
+use pmd_script_entry_list::{ParseOptions, ScriptEntry, ScriptEntryList, ScriptEntryListError, StringOrder, WriteOptions};
+use std::io::Cursor;
+
+mod common;
+use common::{sample_list, single_entry_list, write_with};
+
+#[test]
+fn empty_input_is_too_small() {
+    let result = ScriptEntryList::new_from_file(&mut Cursor::new(Vec::new()));
+    assert!(matches!(result, Err(ScriptEntryListError::TooSmall { len: 0 })));
+}
+
+#[test]
+fn short_input_is_too_small() {
+    let result = ScriptEntryList::new_from_file(&mut Cursor::new(b"SIR0\x10\0\0\0\0\0".to_vec()));
+    assert!(matches!(result, Err(ScriptEntryListError::TooSmall { len: 10 })));
+}
+
+#[test]
+fn nul_in_path_is_rejected_on_write() {
+    let list = ScriptEntryList::from_entries(vec![ScriptEntry {
+        entity_name: "NPC".to_string(),
+        map_name: "map".to_string(),
+        lua_path: "script\0.lua".to_string(),
+        plb_path: "script.plb".to_string(),
+        flags: [0; 4],
+    }]);
+    let result = list.write_to_file(&mut Cursor::new(Vec::new()));
+    assert!(matches!(result, Err(ScriptEntryListError::UnencodableString(ref string)) if string == "script\0.lua"));
+}
+
+#[test]
+fn long_strings_have_no_default_limit() {
+    let mut list = ScriptEntryList::from_entries(vec![ScriptEntry {
+        entity_name: "N".repeat(5000),
+        map_name: "map".to_string(),
+        lua_path: "L".repeat(5000),
+        plb_path: "script.plb".to_string(),
+        flags: [0; 4],
+    }]);
+    let mut buffer = Cursor::new(Vec::new());
+    list.write_to_file_verified(&mut buffer).unwrap();
+    assert_eq!(ScriptEntryList::new_from_file(&mut buffer).unwrap(), list);
+
+    // the limit is only checked when set
+    let options = ParseOptions {
+        max_string_len: Some(100),
+        ..ParseOptions::default()
+    };
+    let offset = list.plan_layout().entries[0].entity_name;
+    let result = ScriptEntryList::new_from_file_with_options(&mut buffer, &options);
+    assert!(matches!(result, Err(ScriptEntryListError::UnterminatedString { offset: found }) if found == offset));
+
+    list.entries[0].entity_name = "N".repeat(100);
+    list.entries[0].lua_path = "script.lua".to_string();
+    let mut buffer = Cursor::new(Vec::new());
+    list.write_to_file(&mut buffer).unwrap();
+    assert_eq!(ScriptEntryList::new_from_file_with_options(&mut buffer, &options).unwrap().0, list);
+}
+
+#[test]
+fn append_from_truncated_file_keeps_the_list() {
+    let mut list = single_entry_list();
+    let original = list.clone();
+    let other = sample_list();
+    let options = WriteOptions {
+        string_order: StringOrder::FirstReferenced,
+        ..WriteOptions::default()
+    };
+    let plan = other.plan_layout_with_options(&options).unwrap();
+    // cut in the middle of the last string, only used by the last entry, so the first entries are parsed before the
+    // failure
+    let last_string = plan.strings.iter().max_by_key(|string| string.offset).unwrap();
+    assert_eq!(last_string.value, other.entries[2].plb_path);
+    let buffer = write_with(&other, &options);
+    let truncated = buffer[..(last_string.offset + 1) as usize].to_vec();
+
+    assert!(list.append_from_file(&mut Cursor::new(truncated)).is_err());
+    assert_eq!(list, original);
+    // a valid file is still appended
+    assert_eq!(list.append_from_file(&mut Cursor::new(buffer)).unwrap(), 3);
+    assert_eq!(list.entries[1..], other.entries[..]);
+}
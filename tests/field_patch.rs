@@ -0,0 +1,98 @@
+//! Changing a single field of an existing file.
+
+use pmd_script_entry_list::{EntryField, FieldPatchOutcome, ScriptEntryList, ScriptEntryListError, WriteOptions};
+use std::io::Cursor;
+
+mod common;
+use common::{entry, patch_u32, write, write_with};
+
+#[test]
+fn shorter_string_is_patched_in_place() {
+    let list = ScriptEntryList::from_entries(vec![entry("A", "a_long_path.lua", [0; 4]), entry("B", "b.lua", [0; 4])]);
+    let original = write(&list);
+    let layout = list.plan_layout().entries[0].clone();
+
+    let mut file = Cursor::new(original.clone());
+    let outcome = ScriptEntryList::patch_entry_field(&mut file, 0, EntryField::LuaPath("x.lua".to_string())).unwrap();
+    assert_eq!(outcome, FieldPatchOutcome::InPlace);
+    let patched = file.into_inner();
+    assert_eq!(patched.len(), original.len());
+
+    // the rest of the old string is zeroed, nothing else changed
+    let start = layout.lua_path as usize;
+    let old_len = ("a_long_path.lua".len() + 1) * 2;
+    let new_len = ("x.lua".len() + 1) * 2;
+    assert!(patched[start + new_len..start + old_len].iter().all(|byte| *byte == 0));
+    assert_eq!(patched[..start], original[..start]);
+    assert_eq!(patched[start + old_len..], original[start + old_len..]);
+
+    let mut expected = list.clone();
+    expected.entries[0].lua_path = "x.lua".to_string();
+    assert_eq!(ScriptEntryList::new_from_file(&mut Cursor::new(patched)).unwrap(), expected);
+}
+
+#[test]
+fn flags_are_patched_in_place() {
+    let list = ScriptEntryList::from_entries(vec![entry("A", "a.lua", [1, 2, 3, 4]), entry("B", "b.lua", [5, 6, 7, 8])]);
+    let mut file = Cursor::new(write(&list));
+    let outcome = ScriptEntryList::patch_entry_field(&mut file, 1, EntryField::Flags([9, 10, 11, 12])).unwrap();
+    assert_eq!(outcome, FieldPatchOutcome::InPlace);
+
+    let mut expected = list.clone();
+    expected.entries[1].flags = [9, 10, 11, 12];
+    assert_eq!(ScriptEntryList::new_from_file(&mut file).unwrap(), expected);
+}
+
+#[test]
+fn shared_string_forces_a_rewrite() {
+    let shared = "a_rather_long_shared_path.lua";
+    let list = ScriptEntryList::from_entries(vec![entry("A", shared, [0; 4]), entry("B", shared, [0; 4])]);
+    // padded, so the rewritten file is shorter
+    let original = write_with(&list, &WriteOptions {
+        final_alignment: 256,
+        ..WriteOptions::default()
+    });
+
+    let mut file = Cursor::new(original.clone());
+    let outcome = ScriptEntryList::patch_entry_field(&mut file, 0, EntryField::LuaPath("x.lua".to_string())).unwrap();
+    let len = match outcome {
+        FieldPatchOutcome::Rewritten { len } => len as usize,
+        other => panic!("unexpected outcome {:?}", other),
+    };
+    let mut expected = list.clone();
+    expected.entries[0].lua_path = "x.lua".to_string();
+    assert_eq!(len, write(&expected).len());
+
+    // the stale tail is left for the caller to truncate
+    let patched = file.into_inner();
+    assert!(len < original.len());
+    assert_eq!(patched.len(), original.len());
+    assert_eq!(patched[len..], original[len..]);
+    assert_eq!(ScriptEntryList::new_from_file(&mut Cursor::new(&patched[..len])).unwrap(), expected);
+}
+
+#[test]
+fn null_pointer_forces_a_rewrite() {
+    let list = ScriptEntryList::from_entries(vec![entry("A", "a.lua", [0; 4])]);
+    let mut original = write(&list);
+    // the plb path is read as an empty string, there is no space to write to
+    patch_u32(&mut original, list.plan_layout().entries[0].record + 12, 0);
+
+    let mut file = Cursor::new(original);
+    let outcome = ScriptEntryList::patch_entry_field(&mut file, 0, EntryField::PlbPath("b.plb".to_string())).unwrap();
+    assert!(matches!(outcome, FieldPatchOutcome::Rewritten { .. }));
+
+    let mut expected = list.clone();
+    expected.entries[0].plb_path = "b.plb".to_string();
+    assert_eq!(ScriptEntryList::new_from_file(&mut file).unwrap(), expected);
+}
+
+#[test]
+fn out_of_range_index() {
+    let list = ScriptEntryList::from_entries(vec![entry("A", "a.lua", [0; 4])]);
+    let original = write(&list);
+    let mut file = Cursor::new(original.clone());
+    let result = ScriptEntryList::patch_entry_field(&mut file, 1, EntryField::Flags([1; 4]));
+    assert!(matches!(result, Err(ScriptEntryListError::InvalidEntryIndex(1))));
+    assert_eq!(file.into_inner(), original);
+}
@@ -0,0 +1,60 @@
+//! Parsing of small files, checked in under `tests/fixtures`.
+//!
+//! They are synthetic (written by this crate), so they also catch unintended changes of the writer output.
+
+use pmd_script_entry_list::{ScriptEntry, ScriptEntryList, WriteOptions};
+use std::io::Cursor;
+use std::path::Path;
+
+fn load_fixture(name: &str) -> Vec<u8> {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests").join("fixtures").join(name);
+    std::fs::read(&path).unwrap_or_else(|err| panic!("can't read the fixture {:?}: {}", path, err))
+}
+
+fn parse(buffer: &[u8]) -> ScriptEntryList {
+    ScriptEntryList::new_from_file(&mut Cursor::new(buffer)).unwrap()
+}
+
+fn rewrite(list: &ScriptEntryList, options: &WriteOptions) -> Vec<u8> {
+    let mut buffer = Cursor::new(Vec::new());
+    list.write_to_file_with_options(&mut buffer, options).unwrap();
+    buffer.into_inner()
+}
+
+#[test]
+fn empty() {
+    let buffer = load_fixture("empty.bin");
+    let list = parse(&buffer);
+    assert!(list.is_empty());
+    assert_eq!(rewrite(&list, &WriteOptions::default()), buffer);
+}
+
+#[test]
+fn single() {
+    let buffer = load_fixture("single.bin");
+    let list = parse(&buffer);
+    assert_eq!(list.entries, vec![ScriptEntry {
+        entity_name: "NPC_PARTNER".to_string(),
+        map_name: "P01P01A".to_string(),
+        lua_path: "SCRIPT/P01P01A/partner.lua".to_string(),
+        plb_path: "SCRIPT/P01P01A/partner.plb".to_string(),
+        flags: [1, 0, 0, 0x8000_0000],
+    }]);
+    assert_eq!(rewrite(&list, &WriteOptions::default()), buffer);
+}
+
+#[test]
+fn shared_strings() {
+    let buffer = load_fixture("shared_strings.bin");
+    assert_eq!(buffer.len() % 16, 0);
+    let list = parse(&buffer);
+    assert_eq!(list.len(), 3);
+    assert_eq!(list.entries[0].entity_name, "NPC_A");
+    assert_eq!(list.entries[1].lua_path, list.entries[0].lua_path);
+    assert_eq!(list.entries[2].flags, [0xFFFF_FFFF, 0, 0, 0]);
+    let options = WriteOptions {
+        final_alignment: 16,
+        ..WriteOptions::default()
+    };
+    assert_eq!(rewrite(&list, &options), buffer);
+}
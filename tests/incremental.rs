@@ -0,0 +1,244 @@
+//! Rewriting a file while keeping the layout of the original.
+
+use pmd_script_entry_list::{FieldCodecs, ParseOptions, ScriptEntryList, ScriptEntryListError, Utf16LeCodec, WriteOptions};
+use std::io::Cursor;
+use std::sync::Arc;
+
+mod common;
+use common::{contains, entry, patch_u32, sample_list, write, write_with};
+
+/// Parse `written` in strict mode, checking there is no warning either
+fn parse_strict(written: &[u8], options: &ParseOptions) -> ScriptEntryList {
+    let options = ParseOptions {
+        strict: true,
+        ..options.clone()
+    };
+    let (list, warnings) = ScriptEntryList::new_from_file_with_options(&mut Cursor::new(written), &options).unwrap();
+    assert_eq!(warnings, Vec::new());
+    list
+}
+
+#[test]
+fn changed_entry_keeps_existing_strings() {
+    let original_list = ScriptEntryList::from_entries(vec![entry("A", "a.lua", [1, 2, 3, 4]), entry("B", "b.lua", [1, 2, 3, 4])]);
+    let original = write(&original_list);
+    let original_plan = original_list.plan_layout();
+
+    let mut list = original_list.clone();
+    list.entries[1].lua_path = "new.lua".to_string();
+    list.entries[1].flags = [5, 6, 7, 8];
+    let mut buffer = Cursor::new(Vec::new());
+    list.write_incremental(&original, &mut buffer).unwrap();
+    let written = buffer.into_inner();
+
+    // the original string pool is left as is, except for the now unused string, that is zeroed
+    for string in &original_plan.strings {
+        let range = string.offset as usize..(string.offset + string.len) as usize;
+        if string.value == "b.lua" {
+            assert!(written[range].iter().all(|byte| *byte == 0));
+        } else {
+            assert_eq!(written[range.clone()], original[range]);
+        };
+    }
+
+    assert_eq!(parse_strict(&written, &ParseOptions::default()), list);
+    let (parsed, layouts) = ScriptEntryList::new_from_file_with_layout(&mut Cursor::new(written)).unwrap();
+    assert_eq!(parsed, list);
+    assert_eq!(layouts[0], original_plan.entries[0]);
+    assert!(layouts[1].lua_path >= original_plan.footer);
+}
+
+#[test]
+fn added_entry_round_trip() {
+    let original_list = ScriptEntryList::from_entries(vec![entry("A", "a.lua", [1, 2, 3, 4])]);
+    let original = write(&original_list);
+
+    let mut list = original_list.clone();
+    list.entries.push(entry("B", "a.lua", [1, 2, 3, 4]));
+    let mut buffer = Cursor::new(Vec::new());
+    list.write_incremental(&original, &mut buffer).unwrap();
+
+    assert_eq!(parse_strict(&buffer.into_inner(), &ParseOptions::default()), list);
+}
+
+#[test]
+fn removed_entry_data_is_reclaimed() {
+    let mut removed = entry("UNIQUE_NAME", "unique.lua", [1, 2, 3, 4]);
+    removed.flags = [0xDEAD_BEEF, 0xCAFE_F00D, 0x1234_5678, 0x0BAD_F00D];
+    let original_list = ScriptEntryList::from_entries(vec![entry("A", "a.lua", [1, 2, 3, 4]), removed.clone(), entry("B", "a.lua", [1, 2, 3, 4])]);
+    let original = write(&original_list);
+    let unique_flags: Vec<u8> = removed.flags.iter().flat_map(|flag| flag.to_le_bytes()).collect();
+    assert!(contains(&original, &unique_flags));
+
+    let mut list = original_list.clone();
+    list.entries.remove(1);
+
+    let mut kept = Cursor::new(Vec::new());
+    list.write_incremental(&original, &mut kept).unwrap();
+    // without compacting, the orphaned flag block is zeroed but still takes place
+    assert!(!contains(kept.get_ref(), &unique_flags));
+    assert_eq!(parse_strict(kept.get_ref(), &ParseOptions::default()), list);
+
+    let mut buffer = Cursor::new(Vec::new());
+    let reclaimed = list.write_incremental_compacting(&original, &mut buffer).unwrap();
+    let written = buffer.into_inner();
+    assert!(reclaimed > 0);
+    assert!(written.len() < kept.get_ref().len());
+    for needle in [&b"UNIQUE_NAME"[..], b"unique.lua", &unique_flags] {
+        assert!(!contains(&written, needle));
+    }
+    let utf16: Vec<u8> = "unique.lua".encode_utf16().flat_map(|unit| unit.to_le_bytes()).collect();
+    assert!(!contains(&written, &utf16));
+
+    assert_eq!(parse_strict(&written, &ParseOptions::default()), list);
+}
+
+#[test]
+fn compacting_an_unchanged_list_keeps_it_identical() {
+    let list = ScriptEntryList::from_entries(vec![entry("A", "a.lua", [1, 2, 3, 4]), entry("B", "b.lua", [1, 2, 3, 4])]);
+    let original = write(&list);
+    let mut buffer = Cursor::new(Vec::new());
+    assert_eq!(list.write_incremental_compacting(&original, &mut buffer).unwrap(), 0);
+    assert_eq!(buffer.get_ref(), &original);
+    assert_eq!(parse_strict(&buffer.into_inner(), &ParseOptions::default()), list);
+}
+
+#[test]
+fn original_alignment_is_kept() {
+    let original_list = ScriptEntryList::from_entries(vec![entry("A", "a.lua", [1, 2, 3, 4])]);
+    let original = write_with(&original_list, &WriteOptions {
+        final_alignment: 16,
+        ..WriteOptions::default()
+    });
+
+    let mut list = original_list.clone();
+    list.entries[0].lua_path = "a_longer_path.lua".to_string();
+    let mut buffer = Cursor::new(Vec::new());
+    list.write_incremental(&original, &mut buffer).unwrap();
+    let written = buffer.into_inner();
+    assert_eq!(written.len() % 16, 0);
+    assert!(written.len() > original.len());
+    assert_eq!(parse_strict(&written, &ParseOptions::default()), list);
+}
+
+#[test]
+fn incremental_write_with_options() {
+    let options = WriteOptions {
+        magic: *b"SIR1",
+        codecs: FieldCodecs {
+            entity_name: Arc::new(Utf16LeCodec),
+            ..FieldCodecs::default()
+        },
+        final_alignment: 8,
+        ..WriteOptions::default()
+    };
+    let parse_options = ParseOptions {
+        magic: options.magic,
+        codecs: options.codecs.clone(),
+        ..ParseOptions::default()
+    };
+    let original_list = ScriptEntryList::from_entries(vec![entry("A", "a.lua", [1, 2, 3, 4]), entry("B", "b.lua", [1, 2, 3, 4])]);
+    let original = write_with(&original_list, &options);
+
+    let mut list = original_list.clone();
+    list.entries[1].entity_name = "NEW".to_string();
+    list.entries.remove(0);
+    for compact in [false, true] {
+        let mut buffer = Cursor::new(Vec::new());
+        if compact {
+            list.write_incremental_compacting_with_options(&original, &mut buffer, &options).unwrap();
+        } else {
+            list.write_incremental_with_options(&original, &mut buffer, &options).unwrap();
+        };
+        let written = buffer.into_inner();
+        assert_eq!(&written[..4], b"SIR1");
+        assert_eq!(written.len() % 8, 0);
+        assert_eq!(parse_strict(&written, &parse_options), list);
+    }
+}
+
+#[test]
+fn original_alignment_with_a_zero_byte_in_the_footer() {
+    let original_list = ScriptEntryList::from_entries(vec![entry("A", "a.lua", [1, 2, 3, 4])]);
+    // the footer ends with [0x81, 0x00], which isn't padding
+    let options = WriteOptions {
+        content_header_len: 132,
+        final_alignment: 4,
+        ..WriteOptions::default()
+    };
+    let original = write_with(&original_list, &options);
+    let footer = original_list.plan_layout_with_options(&options).unwrap().footer as usize;
+    assert_eq!(original[footer..].len() % 4, 0);
+
+    let mut list = original_list.clone();
+    list.entries[0].lua_path = "abc.lua".to_string();
+    let mut buffer = Cursor::new(Vec::new());
+    list.write_incremental(&original, &mut buffer).unwrap();
+    let written = buffer.into_inner();
+    assert_eq!(written.len() % 4, 0);
+    assert_eq!(parse_strict(&written, &ParseOptions::default()), list);
+}
+
+#[test]
+fn original_with_a_footer_pointer_inside_the_data() {
+    let list = sample_list();
+    let original = write(&list);
+    let footer = list.plan_layout().footer as u32;
+    for bad_footer in (0..footer).step_by(4) {
+        let mut malformed = original.clone();
+        patch_u32(&mut malformed, 8, bad_footer);
+        match list.write_incremental(&malformed, &mut Cursor::new(Vec::new())) {
+            Err(ScriptEntryListError::FooterInsideData { footer, data_end }) => {
+                assert_eq!(footer, bad_footer as u64);
+                assert!(data_end > footer);
+            },
+            other => panic!("footer {}: {:?}", bad_footer, other),
+        };
+        assert!(list.write_incremental_compacting(&malformed, &mut Cursor::new(Vec::new())).is_err());
+    }
+}
+
+#[test]
+fn original_with_a_footer_pointer_past_the_end() {
+    let list = sample_list();
+    let mut malformed = write(&list);
+    let len = malformed.len() as u64;
+    patch_u32(&mut malformed, 8, len as u32 + 4);
+    assert!(matches!(
+        list.write_incremental(&malformed, &mut Cursor::new(Vec::new())),
+        Err(ScriptEntryListError::OutOfBounds { offset, len: found_len }) if offset == len + 4 && found_len == len
+    ));
+}
+
+#[test]
+fn compacting_an_original_with_a_footer_pointer_before_its_strings() {
+    let mut list = sample_list();
+    let mut original = write(&list);
+    // inside the entry records, so every string and flag block is past it
+    patch_u32(&mut original, 8, 80);
+    list.entries.remove(1);
+    assert!(matches!(
+        list.write_incremental_compacting(&original, &mut Cursor::new(Vec::new())),
+        Err(ScriptEntryListError::FooterInsideData { footer: 80, .. })
+    ));
+}
+
+#[test]
+fn renamed_entity_reusing_another_string_leaves_no_garbage() {
+    let mut original_entry = entry("b", "a.lua", [1, 2, 3, 4]);
+    original_entry.map_name = "a".to_string();
+    let original_list = ScriptEntryList::from_entries(vec![original_entry]);
+    let original = write(&original_list);
+
+    let mut list = original_list.clone();
+    list.entries[0].entity_name = "a".to_string();
+    for compact in [false, true] {
+        let mut buffer = Cursor::new(Vec::new());
+        if compact {
+            list.write_incremental_compacting(&original, &mut buffer).unwrap();
+        } else {
+            list.write_incremental(&original, &mut buffer).unwrap();
+        };
+        assert_eq!(parse_strict(&buffer.into_inner(), &ParseOptions::default()), list);
+    }
+}
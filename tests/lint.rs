@@ -0,0 +1,69 @@
+//! Checks of the content of a list, that don't need the file it was read from.
+
+use pmd_script_entry_list::{FieldCodecs, FlagLint, LengthWarning, ScriptEntryList, SeparatorReport, SeparatorStyle, StringField, Utf8Codec};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+mod common;
+use common::entry;
+
+#[test]
+fn flags_outside_the_mask() {
+    let list = ScriptEntryList::from_entries(vec![entry("A", "a.lua", [0b11, 0, 0, 0]), entry("B", "b.lua", [0b01, 0, 0, 0x8000_0000])]);
+    let lints = list.lint_flags([0b01, 0, 0, 0]);
+    assert_eq!(lints, vec![
+        FlagLint { entry_index: 0, word: 0, bit: 1 },
+        FlagLint { entry_index: 1, word: 3, bit: 31 },
+    ]);
+    assert!(list.lint_flags([0b11, 0, 0, 0x8000_0000]).is_empty());
+}
+
+#[test]
+fn empty_names() {
+    let mut empty_map = entry("NAMED", "b.lua", [0; 4]);
+    empty_map.map_name = String::new();
+    let list = ScriptEntryList::from_entries(vec![entry("A", "a.lua", [0; 4]), entry(" \t", "c.lua", [0; 4]), empty_map, entry("", "d.lua", [0; 4])]);
+    assert_eq!(list.find_empty_names(), vec![1, 2, 3]);
+}
+
+#[test]
+fn separator_styles() {
+    let with_paths = |paths: &[(&str, &str)]| ScriptEntryList::from_entries(
+        paths.iter()
+            .map(|(lua_path, plb_path)| {
+                let mut result = entry("A", lua_path, [0; 4]);
+                result.plb_path = plb_path.to_string();
+                result
+            })
+            .collect(),
+    );
+    assert_eq!(with_paths(&[("a/b.lua", "a\\b.plb"), ("c.lua", "c.plb")]).separator_style(), SeparatorReport {
+        lua: SeparatorStyle::ForwardSlash,
+        plb: SeparatorStyle::Backslash,
+    });
+    // mixed across different paths
+    assert_eq!(with_paths(&[("a/b.lua", "b.plb"), ("a\\c.lua", "c.plb")]).separator_style(), SeparatorReport {
+        lua: SeparatorStyle::Mixed,
+        plb: SeparatorStyle::None,
+    });
+}
+
+#[test]
+fn string_lengths_per_field() {
+    // "é" is 2 bytes in utf-8, and every character of the paths is 2 bytes in utf-16
+    let list = ScriptEntryList::from_entries(vec![entry("é", "ab.lua", [0; 4]), entry("e", "a.lua", [0; 4])]);
+    let mut limits = HashMap::new();
+    limits.insert(StringField::EntityName, 1);
+    limits.insert(StringField::LuaPath, 10);
+    assert_eq!(list.lint_string_lengths(&limits, &FieldCodecs::default()), vec![
+        LengthWarning { entry_index: 0, field: StringField::EntityName, len: 2 },
+        LengthWarning { entry_index: 0, field: StringField::LuaPath, len: 12 },
+    ]);
+
+    // the length depends on the codec of the field
+    let codecs = FieldCodecs {
+        lua_path: Arc::new(Utf8Codec),
+        ..FieldCodecs::default()
+    };
+    assert_eq!(list.lint_string_lengths(&limits, &codecs), vec![LengthWarning { entry_index: 0, field: StringField::EntityName, len: 2 }]);
+}
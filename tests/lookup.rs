@@ -0,0 +1,192 @@
+use pmd_script_entry_list::{flag_diff, ScriptEntry, ScriptEntryList, ScriptEntryListError};
+use std::collections::HashMap;
+
+mod common;
+use common::entry;
+
+fn list_with_paths(paths: &[&str]) -> ScriptEntryList {
+    ScriptEntryList::from_entries(
+        paths.iter()
+            .map(|path| ScriptEntry {
+                entity_name: path.to_string(),
+                map_name: "map".to_string(),
+                lua_path: format!("{}.lua", path),
+                plb_path: format!("{}.plb", path),
+                flags: [0; 4],
+            })
+            .collect(),
+    )
+}
+
+#[test]
+fn path_glob() {
+    let list = list_with_paths(&["dungeon/d01/enter", "dungeon/d02/enter", "town/enter", "dungeon/d01/exit"]);
+    let names = |pattern: &str| -> Vec<&str> {
+        list.find_by_path_glob(pattern).iter().map(|entry| entry.entity_name.as_str()).collect()
+    };
+    assert_eq!(names("dungeon/*"), vec!["dungeon/d01/enter", "dungeon/d02/enter", "dungeon/d01/exit"]);
+    assert_eq!(names("*/enter.lua"), vec!["dungeon/d01/enter", "dungeon/d02/enter", "town/enter"]);
+    assert_eq!(names("dungeon/d0?/e*r.plb"), vec!["dungeon/d01/enter", "dungeon/d02/enter"]);
+    assert_eq!(names("town/enter.lua"), vec!["town/enter"]);
+    assert_eq!(names("town/enter"), Vec::<&str>::new());
+    assert_eq!(names("*"), vec!["dungeon/d01/enter", "dungeon/d02/enter", "town/enter", "dungeon/d01/exit"]);
+}
+
+#[test]
+fn mutate_every_flags() {
+    let mut list = list_with_paths(&["a", "b", "c"]);
+    for (index, flags) in list.flags_iter_mut().enumerate() {
+        flags[0] = index as u32;
+        flags[3] |= 0x8000_0000;
+    }
+    assert_eq!(list.flag_table(), vec![[0, 0, 0, 0x8000_0000], [1, 0, 0, 0x8000_0000], [2, 0, 0, 0x8000_0000]]);
+}
+
+#[test]
+fn reorder_like_reference() {
+    let list_of = |names: &[(&str, &str)]| ScriptEntryList::from_entries(
+        names.iter()
+            .map(|(name, lua_path)| ScriptEntry {
+                entity_name: name.to_string(),
+                map_name: "map".to_string(),
+                lua_path: lua_path.to_string(),
+                plb_path: "script.plb".to_string(),
+                flags: [0; 4],
+            })
+            .collect(),
+    );
+    let mut list = list_of(&[("X", "x"), ("A", "first_a"), ("B", "b"), ("A", "second_a"), ("Y", "y")]);
+    // a third "A" and a "C" that this list doesn't have
+    let reference = list_of(&[("B", ""), ("A", ""), ("C", ""), ("A", ""), ("A", "")]);
+    list.reorder_like(&reference);
+
+    let order: Vec<&str> = list.entries.iter().map(|entry| entry.lua_path.as_str()).collect();
+    // duplicate names are matched in order, and the entries not in the reference end up last, in their order
+    assert_eq!(order, vec!["b", "first_a", "second_a", "x", "y"]);
+}
+
+#[test]
+fn duplicate_entities_per_map() {
+    let in_map = |name: &str, map: &str| {
+        let mut result = entry(name, "a.lua", [0; 4]);
+        result.map_name = map.to_string();
+        result
+    };
+    // "SHARED" is in two maps, which is fine, but twice in "town"
+    let list = ScriptEntryList::from_entries(vec![
+        in_map("SHARED", "dungeon"),
+        in_map("SHARED", "town"),
+        in_map("OTHER", "town"),
+        in_map("SHARED", "town"),
+        in_map("UNIQUE", "dungeon"),
+    ]);
+    let mut expected = HashMap::new();
+    expected.insert("town".to_string(), vec!["SHARED".to_string()]);
+    assert_eq!(list.duplicate_entities_per_map(), expected);
+
+    let no_duplicate = ScriptEntryList::from_entries(vec![in_map("SHARED", "dungeon"), in_map("SHARED", "town")]);
+    assert!(no_duplicate.duplicate_entities_per_map().is_empty());
+}
+
+#[test]
+fn strip_and_prepend_path_prefix() {
+    let mut only_lua = entry("ONLY_LUA", "mod/a.lua", [0; 4]);
+    only_lua.plb_path = "a.plb".to_string();
+    let mut only_plb = entry("ONLY_PLB", "b.lua", [0; 4]);
+    only_plb.plb_path = "mod/b.plb".to_string();
+    let original = ScriptEntryList::from_entries(vec![only_lua, only_plb, entry("BOTH", "mod/c.lua", [0; 4])]);
+    let mut list = original.clone();
+    // "script.plb", the plb path of "BOTH", doesn't have the prefix
+    assert_eq!(list.strip_path_prefix("mod/"), 3);
+    let paths: Vec<(&str, &str)> = list.entries.iter().map(|entry| (entry.lua_path.as_str(), entry.plb_path.as_str())).collect();
+    assert_eq!(paths, vec![("a.lua", "a.plb"), ("b.lua", "b.plb"), ("c.lua", "script.plb")]);
+
+    // a prefix that no path has leaves the list unchanged
+    let stripped = list.clone();
+    assert_eq!(list.strip_path_prefix("other/"), 0);
+    assert_eq!(list, stripped);
+
+    // once every path has the prefix, stripping it back is the identity
+    let mut round_trip = original.clone();
+    assert_eq!(round_trip.prepend_path_prefix("new/"), 6);
+    assert!(round_trip.entries.iter().all(|entry| entry.lua_path.starts_with("new/") && entry.plb_path.starts_with("new/")));
+    assert_eq!(round_trip.strip_path_prefix("new/"), 6);
+    assert_eq!(round_trip, original);
+}
+
+#[test]
+fn flag_changes_between_lists() {
+    let old = ScriptEntryList::from_entries(vec![entry("CHANGED", "a.lua", [1, 2, 0, 4]), entry("SAME", "b.lua", [5, 0, 0, 0]), entry("REMOVED", "c.lua", [0; 4])]);
+    let new = ScriptEntryList::from_entries(vec![entry("ADDED", "d.lua", [7; 4]), entry("SAME", "b.lua", [5, 0, 0, 0]), entry("CHANGED", "a.lua", [1, 2, 0x8000_0001, 4])]);
+
+    assert_eq!(flag_diff(&old.entries[0], &new.entries[2]), vec![(2, 0), (2, 31)]);
+    assert_eq!(flag_diff(&old.entries[1], &new.entries[1]), Vec::new());
+    // only the entities in both lists are compared
+    assert_eq!(old.flag_changes(&new), vec![("CHANGED".to_string(), vec![(2, 0), (2, 31)])]);
+    assert_eq!(new.flag_changes(&old), vec![("CHANGED".to_string(), vec![(2, 0), (2, 31)])]);
+}
+
+#[test]
+fn flag_only_differences() {
+    let old = ScriptEntryList::from_entries(vec![entry("FLAGS", "a.lua", [1, 0, 0, 0]), entry("PATH", "b.lua", [0; 4]), entry("SAME", "c.lua", [3, 0, 0, 0])]);
+    let new = ScriptEntryList::from_entries(vec![entry("SAME", "c.lua", [3, 0, 0, 0]), entry("PATH", "moved.lua", [2, 0, 0, 0]), entry("FLAGS", "a.lua", [9, 0, 0, 0])]);
+
+    assert!(old.entries[0].eq_ignoring_flags(&new.entries[2]));
+    assert!(!old.entries[1].eq_ignoring_flags(&new.entries[1]));
+    // "PATH" also has a different path, so it isn't a flags-only difference
+    assert_eq!(old.find_flag_only_differences(&new), vec![(&old.entries[0], &new.entries[2])]);
+}
+
+#[test]
+fn grouped_by_map() {
+    let in_map = |name: &str, map: &str| {
+        let mut result = entry(name, "a.lua", [0; 4]);
+        result.map_name = map.to_string();
+        result
+    };
+    let list = ScriptEntryList::from_entries(vec![in_map("A", "town"), in_map("B", "town"), in_map("C", "dungeon"), in_map("D", "town")]);
+    let groups: Vec<(&str, Vec<&str>)> = list.iter_grouped_by_map()
+        .map(|(map, entries)| (map, entries.iter().map(|entry| entry.entity_name.as_str()).collect()))
+        .collect();
+    // "town" isn't consecutive, so it appears twice
+    assert_eq!(groups, vec![("town", vec!["A", "B"]), ("dungeon", vec!["C"]), ("town", vec!["D"])]);
+    assert_eq!(ScriptEntryList::from_entries(Vec::new()).iter_grouped_by_map().count(), 0);
+}
+
+#[test]
+fn duplicate_entry_copies_the_first_match() {
+    let mut list = ScriptEntryList::from_entries(vec![entry("SOURCE", "first.lua", [1, 2, 3, 4]), entry("SOURCE", "second.lua", [0; 4]), entry("OTHER", "b.lua", [0; 4])]);
+    list.duplicate_entry("SOURCE", "COPY").unwrap();
+    assert_eq!(list.entries.len(), 4);
+    assert_eq!(list.entries[3], entry("COPY", "first.lua", [1, 2, 3, 4]));
+
+    assert!(matches!(list.duplicate_entry("SOURCE", "OTHER"), Err(ScriptEntryListError::EntityNameTaken(name)) if name == "OTHER"));
+    assert!(matches!(list.duplicate_entry("MISSING", "NEW"), Err(ScriptEntryListError::EntityNotFound(name)) if name == "MISSING"));
+    assert_eq!(list.entries.len(), 4);
+}
+
+#[test]
+fn remap_flags_by_table() {
+    let mut list = ScriptEntryList::from_entries(vec![entry("A", "a.lua", [1, 0, 0, 0]), entry("B", "b.lua", [2, 0, 0, 0]), entry("C", "c.lua", [1, 0, 0, 0])]);
+    let mut table = HashMap::new();
+    table.insert([1, 0, 0, 0], [5, 6, 7, 8]);
+    // mapped to itself, so not counted as a change
+    table.insert([2, 0, 0, 0], [2, 0, 0, 0]);
+    assert_eq!(list.remap_flags(&table), 2);
+    let flags: Vec<[u32; 4]> = list.entries.iter().map(|entry| entry.flags).collect();
+    assert_eq!(flags, vec![[5, 6, 7, 8], [2, 0, 0, 0], [5, 6, 7, 8]]);
+}
+
+#[test]
+fn path_usage_counts() {
+    let mut same_paths = entry("SAME", "same", [0; 4]);
+    same_paths.plb_path = "same".to_string();
+    // every entry() uses "script.plb"
+    let list = ScriptEntryList::from_entries(vec![entry("A", "a.lua", [0; 4]), entry("B", "a.lua", [0; 4]), same_paths]);
+    let usage = list.path_usage();
+    assert_eq!(usage.len(), 3);
+    assert_eq!(usage["a.lua"], 2);
+    assert_eq!(usage["script.plb"], 2);
+    // counted once for the entry using it as both paths
+    assert_eq!(usage["same"], 1);
+}
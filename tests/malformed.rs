@@ -0,0 +1,231 @@
+//! Parsing of files that this crate wouldn't write, patched by hand.
+
+use pmd_script_entry_list::{ParseOptions, ParseWarning, ScriptEntryList, ScriptEntryListError, WriteOptions};
+use std::io::Cursor;
+
+mod common;
+use common::{patch_u32, sample_list, single_entry_list, write, write_with};
+
+#[test]
+fn null_string_pointer_is_empty_string() {
+    let list = single_entry_list();
+    let mut buffer = write(&list);
+    // the lua path pointer is the third of the entry record
+    patch_u32(&mut buffer, list.plan_layout().entries[0].record + 8, 0);
+
+    let parsed = ScriptEntryList::new_from_file(&mut Cursor::new(buffer)).unwrap();
+    assert_eq!(parsed.entries[0].lua_path, "");
+    assert_eq!(parsed.entries[0].plb_path, "script.plb");
+}
+
+#[test]
+fn stale_entry_count_is_detected() {
+    let list = single_entry_list();
+    let mut buffer = write(&list);
+    assert!(ScriptEntryList::verify_count(&mut Cursor::new(&buffer)).unwrap());
+
+    patch_u32(&mut buffer, list.plan_layout().content_data, 0);
+    assert!(!ScriptEntryList::verify_count(&mut Cursor::new(&buffer)).unwrap());
+}
+
+#[test]
+fn non_zero_padding_is_reported() {
+    let mut list = single_entry_list();
+    // an odd length name, so there is padding before the footer
+    list.entries[0].entity_name = "NPC_1".to_string();
+    let mut buffer = write(&list);
+    let plan = list.plan_layout();
+    let last_string = plan.strings.last().unwrap();
+    let padding = last_string.offset + last_string.len;
+    assert!(padding < plan.footer);
+    buffer[padding as usize] = 0xFF;
+
+    let (parsed, warnings) = ScriptEntryList::new_from_file_with_options(&mut Cursor::new(&buffer), &ParseOptions::default()).unwrap();
+    assert_eq!(parsed, list);
+    assert_eq!(warnings, vec![ParseWarning::NonZeroPadding { offset: padding }]);
+
+    let strict = ParseOptions {
+        strict: true,
+        ..ParseOptions::default()
+    };
+    match ScriptEntryList::new_from_file_with_options(&mut Cursor::new(&buffer), &strict) {
+        Err(ScriptEntryListError::Strict(ParseWarning::NonZeroPadding { offset })) => assert_eq!(offset, padding),
+        other => panic!("unexpected result {:?}", other),
+    };
+}
+
+#[test]
+fn entry_pointer_near_end_of_file() {
+    let list = single_entry_list();
+    let mut buffer = write(&list);
+    let offset = buffer.len() as u64 - 8;
+    patch_u32(&mut buffer, list.plan_layout().entry_pointer_list, offset as u32);
+
+    match ScriptEntryList::new_from_file(&mut Cursor::new(&buffer)) {
+        Err(ScriptEntryListError::TruncatedEntryRecord { index: 0, offset: found }) => assert_eq!(found, offset),
+        other => panic!("unexpected result {:?}", other),
+    };
+}
+
+#[test]
+fn misaligned_utf16_string_in_strict_mode() {
+    // "ØØ" decodes to unpaired surrogates once shifted by a byte, the alignment must be checked before decoding
+    for lua_path in ["script.lua", "ØØ"] {
+        let mut list = single_entry_list();
+        list.entries[0].lua_path = lua_path.to_string();
+        let mut buffer = write(&list);
+        let layout = &list.plan_layout().entries[0];
+        patch_u32(&mut buffer, layout.record + 8, layout.lua_path as u32 + 1);
+
+        let strict = ParseOptions {
+            strict: true,
+            ..ParseOptions::default()
+        };
+        match ScriptEntryList::new_from_file_with_options(&mut Cursor::new(&buffer), &strict) {
+            Err(ScriptEntryListError::MisalignedUtf16 { offset }) => assert_eq!(offset, layout.lua_path + 1),
+            other => panic!("unexpected result {:?}", other),
+        };
+
+        // without strict mode, the string is still read if it can be
+        let lenient = ScriptEntryList::new_from_file_with_options(&mut Cursor::new(&buffer), &ParseOptions::default());
+        match lenient {
+            Ok((_, warnings)) => assert!(warnings.contains(&ParseWarning::MisalignedUtf16 { offset: layout.lua_path + 1 })),
+            Err(ScriptEntryListError::MisalignedUtf16 { offset }) => {
+                assert_eq!(lua_path, "ØØ");
+                assert_eq!(offset, layout.lua_path + 1);
+            },
+            other => panic!("unexpected result {:?}", other),
+        };
+    }
+}
+
+#[test]
+fn utf16_string_with_an_odd_trailing_byte() {
+    let mut list = single_entry_list();
+    list.entries[0].lua_path = "abcd.lua".to_string();
+    let mut buffer = write(&list);
+    let layout = &list.plan_layout().entries[0];
+    // "a" then a lone 0x62 byte before a two-byte NUL, read as a single string with the utf-16 "cd.lua" that follows
+    patch_u32(&mut buffer, layout.lua_path + 2, u32::from_le_bytes([0x62, 0, 0, 0x63]));
+
+    let strict = ParseOptions {
+        strict: true,
+        ..ParseOptions::default()
+    };
+    match ScriptEntryList::new_from_file_with_options(&mut Cursor::new(&buffer), &strict) {
+        Err(ScriptEntryListError::MisalignedUtf16 { offset }) => assert_eq!(offset, layout.lua_path),
+        other => panic!("unexpected result {:?}", other),
+    };
+    let (_, warnings) = ScriptEntryList::new_from_file_with_options(&mut Cursor::new(&buffer), &ParseOptions::default()).unwrap();
+    assert_eq!(warnings, vec![ParseWarning::MisalignedUtf16 { offset: layout.lua_path }]);
+
+    // an unpatched string ending in an ascii character isn't mistaken for one
+    assert_eq!(ScriptEntryList::new_from_file_with_options(&mut Cursor::new(write(&list)), &strict).unwrap().0, list);
+}
+
+#[test]
+fn stale_entry_count_is_repaired() {
+    let list = sample_list();
+    let repair = ParseOptions {
+        repair_entry_count: true,
+        ..ParseOptions::default()
+    };
+    for options in [
+        WriteOptions::default(),
+        WriteOptions {
+            pointer_list_sentinel: true,
+            ..WriteOptions::default()
+        },
+        WriteOptions {
+            content_header_len: 16,
+            ..WriteOptions::default()
+        },
+    ] {
+        let content_data = list.plan_layout_with_options(&options).unwrap().content_data;
+        for stale_count in [0, 1, 5] {
+            let mut buffer = write_with(&list, &options);
+            patch_u32(&mut buffer, content_data, stale_count);
+            let (parsed, warnings) = ScriptEntryList::new_from_file_with_options(&mut Cursor::new(&buffer), &repair).unwrap();
+            assert_eq!(parsed, list);
+            assert_eq!(warnings, vec![ParseWarning::EntryCountMismatch {
+                header: stale_count,
+                pointer_list: 3,
+            }]);
+        }
+
+        // a correct count is left alone
+        let buffer = write_with(&list, &options);
+        let (parsed, warnings) = ScriptEntryList::new_from_file_with_options(&mut Cursor::new(&buffer), &repair).unwrap();
+        assert_eq!(parsed, list);
+        assert!(warnings.is_empty());
+    }
+}
+
+#[test]
+fn partial_parse_keeps_the_entries_before_the_error() {
+    let list = sample_list();
+    let mut buffer = write(&list);
+    // the lua path of the second entry points past the end of the file
+    let past_the_end = buffer.len() as u32 + 16;
+    patch_u32(&mut buffer, list.plan_layout().entries[1].record + 8, past_the_end);
+
+    let (entries, error) = ScriptEntryList::new_from_file_partial(&mut Cursor::new(&buffer));
+    assert_eq!(entries, list.entries[..1]);
+    assert!(matches!(error, Some(ScriptEntryListError::IOError(_))));
+
+    let (entries, error) = ScriptEntryList::new_from_file_partial(&mut Cursor::new(write(&list)));
+    assert_eq!(entries, list.entries);
+    assert!(error.is_none());
+}
+
+#[test]
+fn relocated_content_header() {
+    let list = sample_list();
+    let original = write(&list);
+    let plan = list.plan_layout();
+    // move the content data header between the strings and the footer
+    let content_data = plan.footer as usize;
+    let mut buffer = original[..content_data].to_vec();
+    buffer.extend_from_slice(&original[plan.content_data as usize..plan.content_data as usize + 8]);
+    buffer.extend_from_slice(&original[plan.footer as usize..]);
+    patch_u32(&mut buffer, 4, content_data as u32);
+    patch_u32(&mut buffer, 8, content_data as u32 + 8);
+
+    let (parsed, warnings) = ScriptEntryList::new_from_file_with_options(&mut Cursor::new(&buffer), &ParseOptions::default()).unwrap();
+    assert_eq!(parsed, list);
+    assert_eq!(warnings, vec![ParseWarning::UnexpectedContentDataPointer(content_data as u32)]);
+
+    let strict = ParseOptions {
+        strict: true,
+        ..ParseOptions::default()
+    };
+    match ScriptEntryList::new_from_file_with_options(&mut Cursor::new(&buffer), &strict) {
+        Err(ScriptEntryListError::Strict(ParseWarning::UnexpectedContentDataPointer(offset))) => assert_eq!(offset, content_data as u32),
+        other => panic!("unexpected result {:?}", other),
+    };
+}
+
+#[test]
+fn unterminated_pointer_list_stops_at_the_footer() {
+    let list = single_entry_list();
+    let options = WriteOptions {
+        pointer_list_sentinel: true,
+        ..WriteOptions::default()
+    };
+    let mut buffer = write_with(&list, &options);
+    let sentinel = list.plan_layout_with_options(&options).unwrap().entry_pointer_list + 4;
+    let sentinel_options = ParseOptions {
+        pointer_list_sentinel: true,
+        ..ParseOptions::default()
+    };
+    assert_eq!(ScriptEntryList::new_from_file_with_options(&mut Cursor::new(&buffer), &sentinel_options).unwrap().0, list);
+
+    // the sentinel is overwritten, and the footer pointer moved right after it, so the next pointer would be in the
+    // footer
+    patch_u32(&mut buffer, sentinel, 1);
+    patch_u32(&mut buffer, 8, sentinel as u32 + 4);
+    match ScriptEntryList::new_from_file_with_options(&mut Cursor::new(&buffer), &sentinel_options) {
+        Err(ScriptEntryListError::UnterminatedPointerList { offset }) => assert_eq!(offset, sentinel - 4),
+        other => panic!("unexpected result {:?}", other),
+    };
+}
@@ -0,0 +1,372 @@
+use pmd_script_entry_list::{EntryOrder, FooterWriter, ParseOptions, ParseWarning, ScriptEntryList, ScriptEntryListCodec, ScriptEntryListError, StringOrder, WriteOptions};
+use std::io::Cursor;
+use std::sync::Arc;
+
+mod common;
+use common::{entry, read_u32, sample_list, write_with};
+
+#[test]
+fn without_pointer_list_sentinel() {
+    let list = sample_list();
+    let buffer = write_with(&list, &WriteOptions::default());
+    let plan = list.plan_layout();
+    // the first entry directly follow the pointer list
+    assert_eq!(plan.entries[0].record, plan.entry_pointer_list + 3 * 4);
+    assert_eq!(ScriptEntryList::new_from_file(&mut Cursor::new(buffer)).unwrap(), list);
+}
+
+#[test]
+fn with_pointer_list_sentinel() {
+    let list = sample_list();
+    let options = WriteOptions {
+        pointer_list_sentinel: true,
+        ..WriteOptions::default()
+    };
+    let mut buffer = write_with(&list, &options);
+    let plan = list.plan_layout_with_options(&options).unwrap();
+    assert_eq!(read_u32(&buffer, plan.entry_pointer_list + 3 * 4), 0);
+    assert_eq!(plan.entries[0].record, plan.entry_pointer_list + 4 * 4);
+
+    // the entry count alone is still enough to read it
+    assert_eq!(ScriptEntryList::new_from_file(&mut Cursor::new(buffer.clone())).unwrap(), list);
+
+    // and the sentinel is enough too, even with a wrong count
+    buffer[plan.content_data as usize] = 2;
+    let parse_options = ParseOptions {
+        pointer_list_sentinel: true,
+        ..ParseOptions::default()
+    };
+    let (parsed, warnings) = ScriptEntryList::new_from_file_with_options(&mut Cursor::new(buffer), &parse_options).unwrap();
+    assert_eq!(parsed, list);
+    assert_eq!(warnings, vec![ParseWarning::EntryCountMismatch { header: 2, pointer_list: 3 }]);
+}
+
+#[test]
+fn larger_content_header() {
+    let list = sample_list();
+    let options = WriteOptions {
+        content_header_len: 16,
+        ..WriteOptions::default()
+    };
+    let buffer = write_with(&list, &options);
+    let plan = list.plan_layout_with_options(&options).unwrap();
+    assert_eq!(plan.entry_pointer_list, plan.content_data + 16);
+    assert_eq!(read_u32(&buffer, plan.content_data + 8), 0);
+    assert_eq!(ScriptEntryList::new_from_file(&mut Cursor::new(buffer)).unwrap(), list);
+
+    let options = WriteOptions {
+        content_header_len: 6,
+        ..WriteOptions::default()
+    };
+    assert!(list.plan_layout_with_options(&options).is_err());
+}
+
+#[test]
+fn dedup_flags_only_share_identical_blocks() {
+    let mut list = sample_list();
+    list.entries[0].flags = [1, 2, 3, 4];
+    list.entries[1].flags = [1, 2, 3, 4];
+    // same words, in another order
+    list.entries[2].flags = [4, 3, 2, 1];
+    let options = WriteOptions {
+        dedup_flags: true,
+        ..WriteOptions::default()
+    };
+    let plan = list.plan_layout_with_options(&options).unwrap();
+    assert_eq!(plan.entries[0].flags, plan.entries[1].flags);
+    assert_ne!(plan.entries[0].flags, plan.entries[2].flags);
+
+    let buffer = write_with(&list, &options);
+    assert_eq!(ScriptEntryList::new_from_file(&mut Cursor::new(buffer)).unwrap(), list);
+
+    // without the option, every entry has its own block
+    let plan = list.plan_layout();
+    assert_ne!(plan.entries[0].flags, plan.entries[1].flags);
+}
+
+#[test]
+fn crc32_trailer() {
+    let list = sample_list();
+    let options = WriteOptions {
+        append_crc32: true,
+        ..WriteOptions::default()
+    };
+    let mut buffer = write_with(&list, &options);
+    assert_eq!(buffer.len() as u64, list.plan_layout().total_len + 4);
+    assert!(ScriptEntryList::verify_crc32(&mut Cursor::new(&buffer)).unwrap());
+    assert_eq!(ScriptEntryList::new_from_file(&mut Cursor::new(&buffer)).unwrap(), list);
+
+    buffer[20] ^= 1;
+    assert!(!ScriptEntryList::verify_crc32(&mut Cursor::new(&buffer)).unwrap());
+
+    // the standard check value of CRC-32
+    let mut check = b"123456789".to_vec();
+    check.extend_from_slice(&0xCBF4_3926u32.to_le_bytes());
+    assert!(ScriptEntryList::verify_crc32(&mut Cursor::new(check)).unwrap());
+}
+
+#[test]
+fn strings_in_first_referenced_order() {
+    let list = sample_list();
+    let options = WriteOptions {
+        string_order: StringOrder::FirstReferenced,
+        ..WriteOptions::default()
+    };
+    let plan = list.plan_layout_with_options(&options).unwrap();
+    let values: Vec<&str> = plan.strings.iter().map(|string| string.value.as_str()).collect();
+    assert_eq!(values, vec![
+        "NPC_0", "map", "script/0.lua", "script/0.plb",
+        "NPC_1", "script/1.lua", "script/1.plb",
+        "NPC_2", "script/2.lua", "script/2.plb",
+    ]);
+    assert!(plan.strings.windows(2).all(|pair| pair[0].offset < pair[1].offset));
+
+    let buffer = write_with(&list, &options);
+    assert_eq!(ScriptEntryList::new_from_file(&mut Cursor::new(buffer)).unwrap(), list);
+}
+
+/// Write each pointer as a plain u32
+#[derive(Debug)]
+struct PlainFooter;
+
+impl FooterWriter for PlainFooter {
+    fn encode(&self, pointers: &[u32]) -> Result<Vec<u8>, ScriptEntryListError> {
+        Ok(pointers.iter().flat_map(|pointer| pointer.to_le_bytes()).collect())
+    }
+}
+
+#[test]
+fn custom_footer_writer() {
+    let list = sample_list();
+    let options = WriteOptions {
+        footer_writer: Arc::new(PlainFooter),
+        ..WriteOptions::default()
+    };
+    let buffer = write_with(&list, &options);
+    let plan = list.plan_layout_with_options(&options).unwrap();
+    // the 3 header pointers, then one per entry and 5 per record
+    assert_eq!(plan.footer_len, (3 + 3 * 6) * 4);
+    assert_eq!(buffer.len() as u64, plan.total_len);
+    assert_eq!(read_u32(&buffer, plan.footer), 4);
+    assert_eq!(read_u32(&buffer, plan.footer + 4), 8);
+    assert_eq!(ScriptEntryList::new_from_file(&mut Cursor::new(buffer)).unwrap(), list);
+}
+
+#[test]
+fn shared_flag_block_is_split_on_rewrite() {
+    let mut list = sample_list();
+    list.entries[1].flags = list.entries[0].flags;
+    let options = WriteOptions {
+        dedup_flags: true,
+        ..WriteOptions::default()
+    };
+    let buffer = write_with(&list, &options);
+    let (mut parsed, layouts) = ScriptEntryList::new_from_file_with_layout(&mut Cursor::new(buffer)).unwrap();
+    assert_eq!(layouts[0].flags, layouts[1].flags);
+
+    parsed.entries[1].flags[2] = 42;
+    for options in &[WriteOptions::default(), options] {
+        let rewritten = write_with(&parsed, options);
+        let (reparsed, layouts) = ScriptEntryList::new_from_file_with_layout(&mut Cursor::new(rewritten)).unwrap();
+        assert_ne!(layouts[0].flags, layouts[1].flags);
+        assert_eq!(reparsed.entries[0].flags, list.entries[0].flags);
+        assert_eq!(reparsed.entries[1].flags[2], 42);
+    }
+}
+
+#[test]
+fn compact_removes_duplicates_and_shares_flags() {
+    let mut expected = sample_list();
+    for entry in &mut expected.entries {
+        entry.flags = [7, 0, 0, 0];
+    }
+    let mut list = expected.clone();
+    list.entries.push(expected.entries[0].clone());
+    let naive_len = write_with(&list, &WriteOptions::default()).len() as u64;
+
+    let report = list.compact();
+    assert_eq!(report.removed_entries, 1);
+    assert_eq!(list, expected);
+    assert_eq!(report.original_len, naive_len);
+
+    let buffer = write_with(&list, &WriteOptions::compact());
+    assert_eq!(report.compact_len, buffer.len() as u64);
+    // the duplicate record and its table slot, plus two of the three identical flag blocks
+    assert!(report.saved() >= 24 + 2 * 16);
+    assert_eq!(ScriptEntryList::new_from_file(&mut Cursor::new(buffer)).unwrap(), list);
+}
+
+#[test]
+fn custom_magic() {
+    let list = sample_list();
+    let codec = ScriptEntryListCodec::default().magic(*b"SIR1");
+    let mut buffer = Cursor::new(Vec::new());
+    codec.write(&mut buffer, &list).unwrap();
+    assert_eq!(&buffer.get_ref()[..4], b"SIR1");
+    assert_eq!(codec.read(&mut buffer).unwrap().0, list);
+
+    match ScriptEntryList::new_from_file(&mut buffer) {
+        Err(ScriptEntryListError::InvalidHeader(magic)) => assert_eq!(&magic, b"SIR1"),
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
+
+#[test]
+fn entries_sorted_by_name() {
+    let list = ScriptEntryList::from_entries(vec![
+        entry("C", "c.lua", [0; 4]),
+        entry("A", "first_a.lua", [0; 4]),
+        entry("B", "b.lua", [0; 4]),
+        entry("A", "second_a.lua", [0; 4]),
+    ]);
+    let before = list.clone();
+    let options = WriteOptions {
+        entry_order: EntryOrder::SortedByName,
+        ..WriteOptions::default()
+    };
+    let buffer = write_with(&list, &options);
+    assert_eq!(list, before);
+
+    let (parsed, layouts) = ScriptEntryList::new_from_file_with_layout(&mut Cursor::new(buffer)).unwrap();
+    let order: Vec<&str> = parsed.entries.iter().map(|entry| entry.lua_path.as_str()).collect();
+    assert_eq!(order, vec!["first_a.lua", "second_a.lua", "b.lua", "c.lua"]);
+    // the records follow the pointer table order
+    assert!(layouts.windows(2).all(|pair| pair[0].record < pair[1].record));
+}
+
+#[test]
+fn custom_magic_in_every_entry_point() {
+    let list = sample_list();
+    let write_options = WriteOptions {
+        magic: *b"SIR1",
+        ..WriteOptions::default()
+    };
+    let parse_options = ParseOptions {
+        magic: *b"SIR1",
+        ..ParseOptions::default()
+    };
+    let buffer = write_with(&list, &write_options);
+    let is_invalid_header = |result: Result<u32, ScriptEntryListError>| matches!(result, Err(ScriptEntryListError::InvalidHeader(magic)) if &magic == b"SIR1");
+
+    assert!(is_invalid_header(ScriptEntryList::read_entry_count(&mut Cursor::new(&buffer))));
+    assert_eq!(ScriptEntryList::read_entry_count_with_options(&mut Cursor::new(&buffer), &parse_options).unwrap(), 3);
+    assert!(ScriptEntryList::verify_count_with_options(&mut Cursor::new(&buffer), &parse_options).unwrap());
+    assert_eq!(ScriptEntryList::new_from_file_limited_with_options(&mut Cursor::new(&buffer), 2, &parse_options).unwrap(), (ScriptEntryList::from_entries(list.entries[..2].to_vec()), true));
+    assert!(ScriptEntryList::debug_dump_offsets_with_options(&mut Cursor::new(&buffer), &parse_options).unwrap().contains("entry 2:"));
+    let borrowed: Vec<_> = ScriptEntryList::parse_borrowed_with_options(&buffer, &parse_options).unwrap()
+        .into_iter()
+        .map(|entry| entry.into_owned())
+        .collect();
+    assert_eq!(borrowed, list.entries);
+    assert_eq!(ScriptEntryList::new_from_slice_parallel_with_options(&buffer, 2, &parse_options).unwrap(), list);
+    let mut input = &buffer[..];
+    assert_eq!(ScriptEntryList::new_from_slice_with_options(&mut input, &parse_options).unwrap().0, list);
+
+    // blocks are split on the configured magic
+    let lists = vec![list.clone(), ScriptEntryList::from_entries(Vec::new()), list];
+    let mut file = Cursor::new(Vec::new());
+    ScriptEntryList::write_all_with_options(&lists, &mut file, &write_options).unwrap();
+    assert_eq!(ScriptEntryList::read_all_with_options(&mut file, &parse_options).unwrap(), (lists, Vec::new()));
+}
+
+#[test]
+fn compact_keeps_the_first_of_each_duplicate() {
+    let unique: Vec<_> = (0..500).map(|id| entry(&format!("NPC_{}", id), "script.lua", [id % 7, 0, 0, 0])).collect();
+    // every entry repeated, interleaved with the others
+    let mut list = ScriptEntryList::from_entries(unique.iter().chain(unique.iter().rev()).cloned().collect());
+    let report = list.compact();
+    assert_eq!(report.removed_entries, 500);
+    assert_eq!(list.entries, unique);
+}
+
+#[test]
+fn slice_parse_with_a_zero_byte_in_the_footer() {
+    let list = sample_list();
+    // the pointer to the entry pointer list is 128 bytes after the previous one, encoded as [0x81, 0x00]
+    let options = WriteOptions {
+        content_header_len: 132,
+        ..WriteOptions::default()
+    };
+    let mut data = write_with(&list, &options);
+    let footer = list.plan_layout_with_options(&options).unwrap().footer as usize;
+    assert_eq!(data[footer..footer + 5], [0x04, 0x04, 0x0C, 0x81, 0x00]);
+    let block_len = data.len();
+    data.extend_from_slice(b"trailer");
+
+    let mut input = &data[..];
+    assert_eq!(ScriptEntryList::new_from_slice(&mut input).unwrap(), list);
+    assert_eq!(input, b"trailer");
+    assert_eq!(data.len() - input.len(), block_len);
+}
+
+#[test]
+fn borrowed_parse_with_the_largest_max_string_len() {
+    let list = sample_list();
+    let buffer = write_with(&list, &WriteOptions::default());
+    let parse_options = ParseOptions {
+        max_string_len: Some(usize::MAX),
+        ..ParseOptions::default()
+    };
+    let borrowed: Vec<_> = ScriptEntryList::parse_borrowed_with_options(&buffer, &parse_options).unwrap()
+        .into_iter()
+        .map(|entry| entry.into_owned())
+        .collect();
+    assert_eq!(borrowed, list.entries);
+}
+
+#[test]
+fn dedup_savings_match_the_written_size() {
+    let list = ScriptEntryList::from_entries(vec![entry("NPC_A", "shared.lua", [0; 4]), entry("NPC_B", "shared.lua", [0; 4])]);
+    let savings = list.dedup_savings();
+    // "map" twice, then "script.plb" and "shared.lua" twice
+    assert_eq!(savings.utf8_bytes, 4);
+    assert_eq!(savings.utf16_bytes, 2 * 22);
+    assert_eq!(savings.total(), 48);
+
+    // the same list with distinct strings of the same length, that can't be deduplicated
+    let mut distinct = list.clone();
+    distinct.entries[1].map_name = "mbp".to_string();
+    distinct.entries[1].lua_path = "shares.lua".to_string();
+    distinct.entries[1].plb_path = "script.plc".to_string();
+    assert_eq!(distinct.dedup_savings().total(), 0);
+
+    let options = WriteOptions::default();
+    let deduplicated = write_with(&list, &options);
+    let undeduplicated = write_with(&distinct, &options);
+    assert_eq!(undeduplicated.len() - deduplicated.len(), savings.total());
+}
+
+#[test]
+fn content_equals_ignores_the_layout() {
+    let list = ScriptEntryList::from_entries(vec![entry("B", "b.lua", [1, 0, 0, 0]), entry("A", "a.lua", [2, 0, 0, 0]), entry("C", "a.lua", [3, 0, 0, 0])]);
+    let reordered = WriteOptions {
+        entry_order: EntryOrder::SortedByName,
+        string_order: StringOrder::FirstReferenced,
+        dedup_flags: true,
+        final_alignment: 16,
+        ..WriteOptions::default()
+    };
+    let default_file = write_with(&list, &WriteOptions::default());
+    let reordered_file = write_with(&list, &reordered);
+    assert_ne!(default_file, reordered_file);
+    let from_default = ScriptEntryList::new_from_file(&mut Cursor::new(default_file)).unwrap();
+    let from_reordered = ScriptEntryList::new_from_file(&mut Cursor::new(reordered_file)).unwrap();
+    assert_ne!(from_default, from_reordered);
+    assert!(from_default.content_equals(&from_reordered));
+
+    let mut changed = from_reordered.clone();
+    changed.entries[0].flags[3] = 1;
+    assert!(!from_default.content_equals(&changed));
+    let mut fewer = from_reordered;
+    fewer.entries.pop();
+    assert!(!from_default.content_equals(&fewer));
+}
+
+#[test]
+fn fits_in_the_written_size() {
+    let list = sample_list();
+    let len = write_with(&list, &WriteOptions::default()).len();
+    assert_eq!(list.byte_size_estimate(), len as u64);
+    assert!(list.fits_in(len));
+    assert!(!list.fits_in(len - 1));
+}
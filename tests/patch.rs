@@ -0,0 +1,61 @@
+use pmd_script_entry_list::{Patch, ScriptEntry, ScriptEntryList};
+
+mod common;
+use common::entry;
+use common::value::{from_value, to_value, Value};
+
+fn sorted(mut list: ScriptEntryList) -> Vec<ScriptEntry> {
+    list.entries.sort_by(|a, b| a.entity_name.cmp(&b.entity_name));
+    list.entries
+}
+
+#[test]
+fn patch_round_trip() {
+    let base = ScriptEntryList::from_entries(vec![
+        entry("KEPT", "kept.lua", [0, 0, 0, 0]),
+        entry("MODIFIED", "old.lua", [1, 0, 0, 0]),
+        entry("REMOVED", "removed.lua", [2, 0, 0, 0]),
+    ]);
+    let new = ScriptEntryList::from_entries(vec![
+        entry("ADDED", "added.lua", [3, 0, 0, 0]),
+        entry("KEPT", "kept.lua", [0, 0, 0, 0]),
+        entry("MODIFIED", "new.lua", [4, 0, 0, 0]),
+    ]);
+
+    let patch = base.make_patch(&new);
+    assert_eq!(patch.added, vec![entry("ADDED", "added.lua", [3, 0, 0, 0])]);
+    assert_eq!(patch.removed, vec!["REMOVED".to_string()]);
+    assert_eq!(patch.modified, vec![entry("MODIFIED", "new.lua", [4, 0, 0, 0])]);
+
+    let mut patched = base.clone();
+    patched.apply_patch(&patch);
+    assert_eq!(sorted(patched), sorted(new));
+}
+
+#[test]
+fn identical_lists_make_an_empty_patch() {
+    let list = ScriptEntryList::from_entries(vec![entry("A", "a.lua", [0, 0, 0, 0]), entry("B", "b.lua", [1, 0, 0, 0])]);
+    assert!(list.make_patch(&list.clone()).is_empty());
+}
+
+#[test]
+fn patch_serde_round_trip() {
+    let base = ScriptEntryList::from_entries(vec![entry("MODIFIED", "old.lua", [1, 0, 0, 0]), entry("REMOVED", "removed.lua", [2, 0, 0, 0])]);
+    let new = ScriptEntryList::from_entries(vec![entry("ADDED", "added.lua", [3, 0, 0, 0]), entry("MODIFIED", "new.lua", [4, 5, 6, 7])]);
+    let patch = base.make_patch(&new);
+
+    let serialized = to_value(&patch).unwrap();
+    match &serialized {
+        Value::Struct(fields) => {
+            let names: Vec<&str> = fields.iter().map(|(name, _)| name.as_str()).collect();
+            assert_eq!(names, ["added", "removed", "modified"]);
+        },
+        other => panic!("unexpected value {:?}", other),
+    };
+    let deserialized: Patch = from_value(serialized).unwrap();
+    assert_eq!(deserialized, patch);
+
+    let mut patched = base.clone();
+    patched.apply_patch(&deserialized);
+    assert_eq!(sorted(patched), sorted(new));
+}
@@ -0,0 +1,265 @@
+//! Randomized round-trip tests: a list written with `write_to_file` should parse back identical.
+
+use pmd_script_entry_list::{ScriptEntry, ScriptEntryList, ScriptEntryListReader};
+use std::io::{Cursor, Seek};
+
+/// A small xorshift generator, so the test is reproducible without extra dependencies.
+struct Rng(u64);
+
+impl Rng {
+    fn next_u64(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    fn below(&mut self, max: u64) -> u64 {
+        self.next_u64() % max
+    }
+
+    fn next_char(&mut self) -> char {
+        // mix ASCII, BMP and non-BMP characters (the last need a surrogate pair in UTF-16)
+        let candidate = match self.below(4) {
+            0 | 1 => self.below(0x7F) as u32 + 1,
+            2 => self.below(0xFFFF) as u32 + 1,
+            _ => self.below(0x10FFFF - 0x10000) as u32 + 0x10000,
+        };
+        std::char::from_u32(candidate).unwrap_or('?')
+    }
+
+    fn next_string(&mut self) -> String {
+        let len = self.below(24);
+        (0..len).map(|_| self.next_char()).collect()
+    }
+
+    fn next_entry(&mut self) -> ScriptEntry {
+        ScriptEntry {
+            entity_name: self.next_string(),
+            map_name: self.next_string(),
+            lua_path: self.next_string(),
+            plb_path: self.next_string(),
+            flags: [
+                self.next_u64() as u32,
+                self.next_u64() as u32,
+                self.next_u64() as u32,
+                self.next_u64() as u32,
+            ],
+        }
+    }
+
+    fn next_list(&mut self) -> ScriptEntryList {
+        let len = self.below(40);
+        ScriptEntryList {
+            entries: (0..len).map(|_| self.next_entry()).collect(),
+        }
+    }
+}
+
+fn round_trip(list: &ScriptEntryList) -> ScriptEntryList {
+    let mut buffer = Cursor::new(Vec::new());
+    list.write_to_file(&mut buffer).unwrap();
+    ScriptEntryList::new_from_file(&mut buffer).unwrap()
+}
+
+#[test]
+fn random_lists_round_trip() {
+    let mut rng = Rng(0x5EED_1234_ABCD_0001);
+    for _ in 0..256 {
+        let list = rng.next_list();
+        assert_eq!(round_trip(&list), list);
+    }
+}
+
+#[test]
+fn shared_strings_round_trip() {
+    // the writer pools identical strings, make sure every entry still points to the right one
+    let mut rng = Rng(0x0DDB_A110);
+    let pool: Vec<String> = (0..4).map(|_| rng.next_string()).collect();
+    for _ in 0..64 {
+        let mut list = rng.next_list();
+        for entry in &mut list.entries {
+            entry.entity_name = pool[rng.below(4) as usize].clone();
+            entry.map_name = pool[rng.below(4) as usize].clone();
+            entry.lua_path = pool[rng.below(4) as usize].clone();
+            entry.plb_path = pool[rng.below(4) as usize].clone();
+        }
+        assert_eq!(round_trip(&list), list);
+    }
+}
+
+#[test]
+fn single_entry_round_trip() {
+    let entry = ScriptEntry {
+        entity_name: "NPC_HELPER".to_string(),
+        map_name: "town_square".to_string(),
+        lua_path: "script/town/helper.lua".to_string(),
+        plb_path: "script/town/helper.plb".to_string(),
+        flags: [1, 0, 0x8000_0000, 42],
+    };
+    let list = entry.clone().to_single_entry_list();
+    assert_eq!(list.entries, vec![entry]);
+    assert_eq!(round_trip(&list), list);
+}
+
+#[test]
+fn boundary_flags_round_trip() {
+    let boundaries = [0, 1, 0x7FFF_FFFF, 0x8000_0000, 0xFFFF_FFFE, u32::MAX];
+    let mut entries = vec![ScriptEntry {
+        entity_name: "MAX".to_string(),
+        map_name: "map".to_string(),
+        lua_path: "max.lua".to_string(),
+        plb_path: "max.plb".to_string(),
+        flags: [u32::MAX; 4],
+    }];
+    for (id, window) in boundaries.windows(4).enumerate() {
+        entries.push(ScriptEntry {
+            entity_name: format!("BOUNDARY_{}", id),
+            map_name: "map".to_string(),
+            lua_path: "boundary.lua".to_string(),
+            plb_path: "boundary.plb".to_string(),
+            flags: [window[0], window[1], window[2], window[3]],
+        });
+    }
+    let list = ScriptEntryList::from_entries(entries);
+    assert_eq!(round_trip(&list), list);
+}
+
+#[test]
+fn output_is_deterministic() {
+    let mut rng = Rng(0xDE7E_4217);
+    for _ in 0..16 {
+        let list = rng.next_list();
+        let mut first = Cursor::new(Vec::new());
+        list.write_to_file(&mut first).unwrap();
+        let mut second = Cursor::new(Vec::new());
+        list.clone().write_to_file(&mut second).unwrap();
+        assert_eq!(first.into_inner(), second.into_inner());
+    }
+}
+
+#[test]
+fn borrowed_parse_matches_owned() {
+    let mut rng = Rng(0xB0B);
+    for _ in 0..64 {
+        let list = rng.next_list();
+        let mut buffer = Cursor::new(Vec::new());
+        list.write_to_file(&mut buffer).unwrap();
+        let buffer = buffer.into_inner();
+        let entries: Vec<ScriptEntry> = ScriptEntryList::parse_borrowed(&buffer).unwrap()
+            .into_iter()
+            .map(|entry| entry.into_owned())
+            .collect();
+        assert_eq!(entries, list.entries);
+    }
+}
+
+#[test]
+fn lazy_reader_matches_full_parse() {
+    let mut rng = Rng(0x1A27);
+    for _ in 0..16 {
+        let list = rng.next_list();
+        let mut buffer = Cursor::new(Vec::new());
+        list.write_to_file(&mut buffer).unwrap();
+        let mut reader = ScriptEntryListReader::new(buffer).unwrap();
+        assert_eq!(reader.len(), list.entries.len());
+        // in reverse, to not depend on the entries being read in order
+        for index in (0..list.entries.len()).rev() {
+            assert_eq!(reader.get(index).unwrap(), list.entries[index]);
+        }
+        assert!(reader.get(list.entries.len()).is_err());
+    }
+}
+
+#[test]
+fn duplicate_entries_round_trip() {
+    let mut rng = Rng(0xD0_0B1E);
+    let entry = rng.next_entry();
+    let list = ScriptEntryList {
+        entries: vec![entry.clone(), entry],
+    };
+
+    let plan = list.plan_layout();
+    // both records are written, only the strings are shared
+    assert_eq!(plan.entries.len(), 2);
+    assert_ne!(plan.entries[0].record, plan.entries[1].record);
+    assert_eq!(plan.entries[0].lua_path, plan.entries[1].lua_path);
+    assert_eq!(round_trip(&list), list);
+}
+
+#[test]
+fn slice_parse_advances_past_the_block() {
+    let mut rng = Rng(0x51_1CE5);
+    for _ in 0..16 {
+        let list = rng.next_list();
+        let mut buffer = Cursor::new(Vec::new());
+        list.write_to_file(&mut buffer).unwrap();
+        let mut data = buffer.into_inner();
+        let block_len = data.len();
+        data.extend_from_slice(b"\xFFafter");
+
+        let mut input = &data[..];
+        assert_eq!(ScriptEntryList::new_from_slice(&mut input).unwrap(), list);
+        assert_eq!(input, &data[block_len..]);
+    }
+}
+
+#[test]
+fn parallel_parse_matches_sequential() {
+    let mut rng = Rng(0x7EAD);
+    for threads in 0..6 {
+        let list = rng.next_list();
+        let mut buffer = Cursor::new(Vec::new());
+        list.write_to_file(&mut buffer).unwrap();
+        assert_eq!(ScriptEntryList::new_from_slice_parallel(&buffer.into_inner(), threads).unwrap(), list);
+    }
+}
+
+#[test]
+fn multiple_blocks_round_trip() {
+    let mut rng = Rng(0xB10C);
+    let lists = vec![rng.next_list(), ScriptEntryList::from_entries(Vec::new()), rng.next_list()];
+    let mut buffer = Cursor::new(Vec::new());
+    ScriptEntryList::write_all(&lists, &mut buffer).unwrap();
+    assert_eq!(buffer.get_ref().len() % 16, 0);
+    assert_eq!(ScriptEntryList::read_all(&mut buffer).unwrap(), lists);
+}
+
+#[test]
+fn flags_bits_round_trip() {
+    let mut rng = Rng(0xB175);
+    for _ in 0..64 {
+        let entry = rng.next_entry();
+        let bits = entry.flags_as_bits();
+        assert_eq!(bits[0], entry.flags[0] & 1 != 0);
+        assert_eq!(bits[127], entry.flags[3] & 0x8000_0000 != 0);
+
+        let mut rebuilt = entry.clone();
+        rebuilt.flags = [0; 4];
+        rebuilt.set_flags_from_bits(&bits);
+        assert_eq!(rebuilt.flags, entry.flags);
+        assert_eq!(rebuilt.flags_as_bits(), bits);
+    }
+}
+
+#[test]
+fn write_at_non_zero_base() {
+    let mut rng = Rng(0xBA5E);
+    let list = rng.next_list();
+    assert!(!list.entries.is_empty());
+    let prefix = b"existing data, not part of the block".to_vec();
+    let base = prefix.len() as u64;
+    let mut buffer = Cursor::new(prefix.clone());
+    list.write_to_file_at(&mut buffer, base).unwrap();
+
+    let mut standalone = Cursor::new(Vec::new());
+    list.write_to_file(&mut standalone).unwrap();
+    let block_len = standalone.get_ref().len() as u64;
+    assert_eq!(buffer.stream_position().unwrap(), base + block_len);
+
+    let data = buffer.into_inner();
+    assert_eq!(data[..prefix.len()], prefix[..]);
+    // pointers are relative to the block, so it is the same as a file written on its own
+    assert_eq!(data[prefix.len()..], standalone.get_ref()[..]);
+    assert_eq!(ScriptEntryList::new_from_file(&mut Cursor::new(&data[prefix.len()..])).unwrap(), list);
+}